@@ -1,58 +1,140 @@
 // src/App.tsx
 import './index.css';
-import React, { useState, useCallback } from 'react';
+import React, { useState, useCallback, useEffect } from 'react';
 import { Routes, Route } from 'react-router-dom';
 import PreferencesWindow from './components/PreferencesWindow';
 import Profile from './components/profile/Profile';
+import ProfileSelection from './components/profile/ProfileSelection';
 import NavigationBar from './components/navigation/navbar/NavigationBar';
 import { DirectoryEntryInfo } from './types/navigation'; // Atualizado para usar o novo tipo
+import { ProfileInfo } from './types';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 interface VocabularyEntry {
-  Italian: string;
-  English: string[];
+  id: string;
+  path: string;
+  italian: string;
+  italianForms: string[];
+  english: string[];
+  tags: string[];
+  partOfSpeech?: string;
+  gender?: string;
+  body: string;
+}
+
+interface DeckSnapshot {
+  directoryPath: string;
+  cards: VocabularyEntry[];
+  errors: { id: string; path: string; message: string }[];
+  stats: { files: number; parsed: number; reused: number; removed: number };
+}
+
+// Progresso salvo de um deck, como serializado pelo backend
+interface VocabularyProgress {
+  current_index: number;
+  total_cards: number;
+  directory_path: string;
+  last_updated: number;
+}
+
+interface RestoredSession {
+  snapshot: DeckSnapshot;
+  progress: VocabularyProgress | null;
 }
 
 const App: React.FC = () => {
   const [selectedDirectoryPath, setSelectedDirectoryPath] = useState<string>('');
   const [vocabularyEntries, setVocabularyEntries] = useState<VocabularyEntry[]>([]);
   const [currentVocabularyIndex, setCurrentVocabularyIndex] = useState(0);
+  // undefined enquanto carrega; null quando o aluno ainda precisa escolher um perfil
+  const [activeProfile, setActiveProfile] = useState<ProfileInfo | null | undefined>(undefined);
+  const activeProfileId = activeProfile?.id;
 
   const handleDirectoryEntriesSelected = useCallback(async (
-    directoryEntries: DirectoryEntryInfo[],
+    _directoryEntries: DirectoryEntryInfo[],
     selectedFolderPath?: string
   ) => {
-    if (selectedFolderPath) {
-      setSelectedDirectoryPath(selectedFolderPath);
+    if (!selectedFolderPath) {
+      return;
     }
 
-    // Adaptar para a nova estrutura DirectoryEntryInfo
-    const markdownFiles = directoryEntries.filter(
-      entry => !entry.isDirectory && entry.path?.endsWith('.md')
-    );
-
-    const vocabularyExtractionPromises = markdownFiles.map(async (markdownFile) => {
-      try {
-        const extractedVocabulary = await invoke<VocabularyEntry>(
-          'extract_vocabulary_fields',
-          { filePath: markdownFile.path }
-        );
-        return extractedVocabulary;
-      } catch (error) {
-        console.error(`Erro ao extrair vocabulário de ${markdownFile.name}:`, error);
-        return null;
-      }
-    });
+    setSelectedDirectoryPath(selectedFolderPath);
+
+    // O backend mantém um índice em cache e só relê arquivos modificados
+    try {
+      const deck = await invoke<DeckSnapshot>('open_deck', {
+        directoryPath: selectedFolderPath
+      });
 
-    const extractionResults = await Promise.all(vocabularyExtractionPromises);
-    const validVocabularyEntries = extractionResults.filter(
-      (result): result is VocabularyEntry => result !== null
-    );
+      deck.errors.forEach(cardError => {
+        console.error(`Erro ao extrair vocabulário de ${cardError.id}:`, cardError.message);
+      });
+
+      setVocabularyEntries(deck.cards);
+    } catch (error) {
+      console.error(`Erro ao abrir o deck ${selectedFolderPath}:`, error);
+      setVocabularyEntries([]);
+    }
 
-    setVocabularyEntries(validVocabularyEntries);
     setCurrentVocabularyIndex(0);
   }, []);
 
+  // O backend reabre o último perfil sem PIN; se todos tiverem PIN, o aluno escolhe um
+  useEffect(() => {
+    invoke<ProfileInfo | null>('get_active_profile')
+      .then(setActiveProfile)
+      .catch(error => {
+        console.error('Erro ao ler o perfil ativo:', error);
+        setActiveProfile(null);
+      });
+
+    const unlistenPromise = listen<ProfileInfo>('profile-switched', (event) => {
+      setActiveProfile(event.payload);
+    });
+
+    return () => {
+      unlistenPromise.then(unlisten => unlisten());
+    };
+  }, []);
+
+  // Reabre o último deck da sessão anterior do perfil na posição salva; uma falha apenas
+  // mantém a tela inicial. Roda na inicialização, assim que o perfil reaberto é conhecido,
+  // e a cada troca de perfil. Não roda no setup do backend porque eventos emitidos ali
+  // chegam antes de a interface escutá-los.
+  useEffect(() => {
+    if (!activeProfileId) {
+      return;
+    }
+
+    let cancelled = false;
+    setSelectedDirectoryPath('');
+    setVocabularyEntries([]);
+
+    invoke<RestoredSession | null>('restore_last_session')
+      .then(session => {
+        if (cancelled || !session) {
+          return;
+        }
+
+        // O deck pode ter perdido cartões desde que a posição foi salva
+        const cardCount = session.snapshot.cards.length;
+        const savedIndex = session.progress?.current_index ?? 0;
+        const restoredIndex = Math.min(Math.max(savedIndex, 0), Math.max(cardCount - 1, 0));
+
+        setSelectedDirectoryPath(session.snapshot.directoryPath);
+        setVocabularyEntries(session.snapshot.cards);
+        setCurrentVocabularyIndex(restoredIndex);
+      })
+      .catch(error => {
+        console.warn('Não foi possível reabrir o último deck:', error);
+      });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [activeProfileId]);
+
   return (
     <div className="bg-[#1e1e1e] text-gray-300 h-screen flex flex-col">
       {/* NavigationBar sempre visível */}
@@ -62,6 +144,9 @@ const App: React.FC = () => {
 
       {/* Conteúdo das rotas */}
       <main className="flex-1 overflow-auto">
+        {activeProfile === null ? (
+          <ProfileSelection onSelected={setActiveProfile} />
+        ) : activeProfile && (
         <Routes>
           <Route path="/" element={
             <div className="flex flex-col items-center justify-center w-full h-full space-y-4">
@@ -88,6 +173,7 @@ const App: React.FC = () => {
           <Route path="/preferences" element={<PreferencesWindow />} />
           <Route path="/profile" element={<Profile />} />
         </Routes>
+        )}
       </main>
     </div>
   );
@@ -2,7 +2,7 @@
 import React, { useState, useEffect, useCallback, useRef } from 'react';
 import { Route, Minus, Maximize2, Minimize2, X, Save, RotateCcw } from 'lucide-react';
 import { getCurrentWindow } from '@tauri-apps/api/window';
-import { usePreferences } from '../hooks/usePreferences';
+import { describeError, usePreferences } from '../hooks/usePreferences';
 
 const currentWindow = getCurrentWindow();
 
@@ -143,7 +143,7 @@ const PreferencesWindow: React.FC = () => {
     } catch (err) {
       console.error('Failed to save preferences:', err);
       setSaveStatus('error');
-      setJsonError(err instanceof Error ? err.message : 'Failed to save preferences');
+      setJsonError(describeError(err).message || 'Failed to save preferences');
 
       // Reset error status after 3 seconds
       setTimeout(() => setSaveStatus('idle'), 3000);
@@ -1,6 +1,5 @@
 // src/components/FileSubmenu.tsx
 import React, { useCallback, useMemo } from 'react';
-import { open } from '@tauri-apps/plugin-dialog';
 import { invoke } from '@tauri-apps/api/core';
 import { DirectoryEntryInfo, SubmenuItem } from '../../types';
 
@@ -16,11 +15,8 @@ const FileSubmenu: React.FC<FileSubmenuProps> = ({
   // Optimized handler for opening and processing a folder
   const handleOpenFolderAction = useCallback(async () => {
     try {
-      // Open directory selection dialog
-      const selectedFolderPath = await open({
-        multiple: false,
-        directory: true,
-      });
+      // Open directory selection dialog; the backend grants access to the chosen folder
+      const selectedFolderPath = await invoke<string | null>('pick_directory');
 
       if (typeof selectedFolderPath === 'string') {
         // Invoke Rust command to list directory contents
@@ -36,6 +32,26 @@ const FileSubmenu: React.FC<FileSubmenuProps> = ({
     }
   }, [onDirectoryEntriesSelected]);
 
+  // Opens a folder that lives inside a git repository as a deck
+  const handleAddLocalRepositoryAction = useCallback(async () => {
+    try {
+      const selectedFolderPath = await invoke<string | null>('pick_directory');
+
+      if (typeof selectedFolderPath === 'string') {
+        // Fails when the folder is not part of a git repository
+        await invoke('open_deck_repository', { directoryPath: selectedFolderPath });
+
+        const directoryEntries = await invoke<DirectoryEntryInfo[]>('list_directory_contents', {
+          directoryPath: selectedFolderPath,
+        });
+
+        onDirectoryEntriesSelected(directoryEntries, selectedFolderPath);
+      }
+    } catch (error) {
+      console.error('Failed to add local repository:', error);
+    }
+  }, [onDirectoryEntriesSelected]);
+
   // Memoized submenu items definition to prevent recreation on each render
   const fileSubmenuItems: SubmenuItem[] = useMemo(() => [
     {
@@ -45,7 +61,8 @@ const FileSubmenu: React.FC<FileSubmenuProps> = ({
     },
     {
       label: 'Add local repository...',
-      shortcut: 'Ctrl+O'
+      shortcut: 'Ctrl+O',
+      action: handleAddLocalRepositoryAction
     },
     {
       label: 'Clone repository...',
@@ -61,7 +78,7 @@ const FileSubmenu: React.FC<FileSubmenuProps> = ({
       label: 'Exit',
       shortcut: 'Alt+F4'
     },
-  ], [handleOpenFolderAction]);
+  ], [handleOpenFolderAction, handleAddLocalRepositoryAction]);
 
   // Optimized menu item renderer
   const renderSubmenuItem = useCallback((submenuItem: SubmenuItem, itemIndex: number) => {
@@ -0,0 +1,114 @@
+// src/components/profile/ProfileSelection.tsx
+import React, { useState, useEffect, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { describeError } from '../../hooks/usePreferences';
+import type { ProfileInfo } from '../../types';
+
+interface ProfileSelectionProps {
+  onSelected: (profile: ProfileInfo) => void;
+}
+
+/**
+ * ProfileSelection Component
+ *
+ * Shown at startup when no profile could be opened automatically
+ * (every profile is protected by a PIN):
+ * - Lists the profiles on this machine
+ * - Asks for the PIN of a protected profile
+ * - Opens the chosen profile through `switch_profile`
+ */
+export const ProfileSelection: React.FC<ProfileSelectionProps> = ({ onSelected }) => {
+  const [profiles, setProfiles] = useState<ProfileInfo[]>([]);
+  const [selectedId, setSelectedId] = useState<string | null>(null);
+  const [pin, setPin] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const [opening, setOpening] = useState(false);
+
+  useEffect(() => {
+    invoke<ProfileInfo[]>('list_profiles')
+      .then(setProfiles)
+      .catch(err => setError(describeError(err).message));
+  }, []);
+
+  const openProfile = useCallback(async (profile: ProfileInfo) => {
+    setError(null);
+
+    try {
+      setOpening(true);
+      const opened = await invoke<ProfileInfo>('switch_profile', {
+        profileId: profile.id,
+        pin: profile.hasPin ? pin : null
+      });
+      onSelected(opened);
+    } catch (err) {
+      setError(describeError(err).message);
+      setPin('');
+    } finally {
+      setOpening(false);
+    }
+  }, [pin, onSelected]);
+
+  return (
+    <div className="flex flex-col items-center justify-center w-full h-full space-y-4">
+      <h1 className="text-2xl font-bold text-white mb-4">Who is studying?</h1>
+
+      <ul className="w-72 space-y-2">
+        {profiles.map(profile => (
+          <li key={profile.id} className="bg-[#2d2d2d] rounded-lg p-3">
+            <button
+              className="w-full text-left text-white disabled:opacity-50"
+              disabled={opening}
+              onClick={() => {
+                // Protected profiles wait for the PIN to be entered
+                if (profile.hasPin) {
+                  setSelectedId(profile.id);
+                  setPin('');
+                  setError(null);
+                  return;
+                }
+
+                openProfile(profile);
+              }}
+            >
+              {profile.name}
+              {profile.hasPin && <span className="ml-2 text-xs text-gray-500">PIN</span>}
+            </button>
+
+            {profile.hasPin && selectedId === profile.id && (
+              <form
+                className="mt-2 flex space-x-2"
+                onSubmit={event => {
+                  event.preventDefault();
+                  openProfile(profile);
+                }}
+              >
+                <input
+                  type="password"
+                  inputMode="numeric"
+                  autoFocus
+                  value={pin}
+                  onChange={event => setPin(event.target.value)}
+                  className="flex-1 bg-[#1e1e1e] text-white rounded px-2 py-1 text-sm"
+                  aria-label={`PIN for ${profile.name}`}
+                />
+                <button
+                  type="submit"
+                  disabled={opening || !pin}
+                  className="px-3 py-1 bg-blue-600 text-white rounded text-sm disabled:opacity-50"
+                >
+                  Open
+                </button>
+              </form>
+            )}
+          </li>
+        ))}
+      </ul>
+
+      {error && (
+        <p className="text-sm text-red-400" role="alert">{error}</p>
+      )}
+    </div>
+  );
+};
+
+export default ProfileSelection;
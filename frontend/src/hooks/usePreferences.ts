@@ -2,6 +2,7 @@
 import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import type { AppError, FieldError } from '../types';
 
 interface PreferencesState {
   [key: string]: any;
@@ -11,6 +12,8 @@ interface UsePreferencesReturn {
   preferences: PreferencesState;
   loading: boolean;
   error: string | null;
+  /** Fields rejected by backend validation in the last failed command */
+  fieldErrors: FieldError[];
   savePreference: (key: string, value: any) => Promise<void>;
   saveAllPreferences: (preferences: PreferencesState) => Promise<void>;
   getPreference: (key: string) => Promise<any>;
@@ -19,14 +22,43 @@ interface UsePreferencesReturn {
   clearAllPreferences: () => Promise<void>;
   hasPreference: (key: string) => Promise<boolean>;
   saveVocabularyProgress: (currentIndex: number, totalCards: number, directoryPath: string) => Promise<void>;
-  getVocabularyProgress: () => Promise<any>;
+  getVocabularyProgress: (directoryPath: string) => Promise<any>;
   refreshPreferences: () => Promise<void>;
 }
 
+/**
+ * Reads an error rejected by `invoke` (a backend `AppError`) or thrown in the frontend
+ */
+export const describeError = (err: unknown): { message: string; fieldErrors: FieldError[] } => {
+  if (err instanceof Error) {
+    return { message: err.message, fieldErrors: [] };
+  }
+
+  if (err && typeof err === 'object' && 'code' in err && 'message' in err) {
+    const appError = err as AppError;
+    return {
+      message: appError.message,
+      fieldErrors: appError.code === 'validation' ? appError.errors : [],
+    };
+  }
+
+  return { message: String(err), fieldErrors: [] };
+};
+
 export const usePreferences = (): UsePreferencesReturn => {
   const [preferences, setPreferences] = useState<PreferencesState>({});
   const [loading, setLoading] = useState<boolean>(true);
   const [error, setError] = useState<string | null>(null);
+  const [fieldErrors, setFieldErrors] = useState<FieldError[]>([]);
+
+  /**
+   * Record a failed command, keeping field-level validation errors
+   */
+  const reportError = useCallback((err: unknown) => {
+    const described = describeError(err);
+    setError(described.message);
+    setFieldErrors(described.fieldErrors);
+  }, []);
 
   /**
    * Load all preferences from backend
@@ -35,17 +67,17 @@ export const usePreferences = (): UsePreferencesReturn => {
     try {
       setLoading(true);
       setError(null);
+      setFieldErrors([]);
 
       const allPrefs = await invoke<PreferencesState>('get_all_preferences');
       setPreferences(allPrefs);
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to load preferences:', err);
     } finally {
       setLoading(false);
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Save a single preference
@@ -53,6 +85,7 @@ export const usePreferences = (): UsePreferencesReturn => {
   const savePreference = useCallback(async (key: string, value: any) => {
     try {
       setError(null);
+      setFieldErrors([]);
 
       await invoke('save_preference', { key, value });
 
@@ -62,12 +95,11 @@ export const usePreferences = (): UsePreferencesReturn => {
         [key]: value
       }));
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to save preference:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Save all preferences at once (for bulk operations)
@@ -75,18 +107,18 @@ export const usePreferences = (): UsePreferencesReturn => {
   const saveAllPreferences = useCallback(async (newPreferences: PreferencesState) => {
     try {
       setError(null);
+      setFieldErrors([]);
 
       await invoke('save_all_preferences', { preferences: newPreferences });
 
       // Update local state
       setPreferences(newPreferences);
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to save all preferences:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Get a specific preference
@@ -94,16 +126,16 @@ export const usePreferences = (): UsePreferencesReturn => {
   const getPreference = useCallback(async (key: string) => {
     try {
       setError(null);
+      setFieldErrors([]);
 
       const value = await invoke('get_preference', { key });
       return value;
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to get preference:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Delete a preference
@@ -111,6 +143,7 @@ export const usePreferences = (): UsePreferencesReturn => {
   const deletePreference = useCallback(async (key: string) => {
     try {
       setError(null);
+      setFieldErrors([]);
 
       await invoke('delete_preference', { key });
 
@@ -121,12 +154,11 @@ export const usePreferences = (): UsePreferencesReturn => {
         return newPrefs;
       });
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to delete preference:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Get all preferences
@@ -134,17 +166,17 @@ export const usePreferences = (): UsePreferencesReturn => {
   const getAllPreferences = useCallback(async () => {
     try {
       setError(null);
+      setFieldErrors([]);
 
       const allPrefs = await invoke<PreferencesState>('get_all_preferences');
       setPreferences(allPrefs);
       return allPrefs;
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to get all preferences:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Clear all preferences
@@ -152,18 +184,18 @@ export const usePreferences = (): UsePreferencesReturn => {
   const clearAllPreferences = useCallback(async () => {
     try {
       setError(null);
+      setFieldErrors([]);
 
       await invoke('clear_all_preferences');
 
       // Update local state
       setPreferences({});
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to clear all preferences:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Check if preference exists
@@ -171,16 +203,16 @@ export const usePreferences = (): UsePreferencesReturn => {
   const hasPreference = useCallback(async (key: string) => {
     try {
       setError(null);
+      setFieldErrors([]);
 
       const exists = await invoke<boolean>('has_preference', { key });
       return exists;
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to check preference:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Save vocabulary progress
@@ -188,49 +220,37 @@ export const usePreferences = (): UsePreferencesReturn => {
   const saveVocabularyProgress = useCallback(async (currentIndex: number, totalCards: number, directoryPath: string) => {
     try {
       setError(null);
+      setFieldErrors([]);
 
+      // Local state is refreshed by the 'preference-updated' event for deckProgress
       await invoke('save_vocabulary_progress', {
         currentIndex,
         totalCards,
         directoryPath
       });
-
-      // Update local state
-      const progressData = {
-        currentIndex,
-        totalCards,
-        directoryPath,
-        lastUpdated: new Date().toISOString()
-      };
-
-      setPreferences(prev => ({
-        ...prev,
-        vocabulary_progress: progressData
-      }));
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to save vocabulary progress:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Get vocabulary progress
    */
-  const getVocabularyProgress = useCallback(async () => {
+  const getVocabularyProgress = useCallback(async (directoryPath: string) => {
     try {
       setError(null);
+      setFieldErrors([]);
 
-      const progress = await invoke('get_vocabulary_progress');
+      const progress = await invoke('get_vocabulary_progress', { directoryPath });
       return progress;
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setError(errorMessage);
+      reportError(err);
       console.error('Failed to get vocabulary progress:', err);
       throw err;
     }
-  }, []);
+  }, [reportError]);
 
   /**
    * Refresh preferences from backend
@@ -294,6 +314,7 @@ export const usePreferences = (): UsePreferencesReturn => {
     preferences,
     loading,
     error,
+    fieldErrors,
     savePreference,
     saveAllPreferences,
     getPreference,
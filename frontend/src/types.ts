@@ -23,3 +23,33 @@ export interface DividerItem {
 
 /** União discriminada para cada linha do submenu */
 export type SubmenuItem = ActionItem | DividerItem;
+
+/** Campo de formulário rejeitado pela validação do backend */
+export interface FieldError {
+  field: string;
+  message: string;
+}
+
+/** Erro devolvido pelos comandos do backend, discriminado por `code` */
+export type AppError =
+  | { code: 'notFound'; message: string }
+  | { code: 'invalidInput'; field: string; message: string }
+  | { code: 'validation'; errors: FieldError[]; message: string }
+  | { code: 'alreadyExists'; path: string; message: string }
+  | { code: 'io'; path: string; message: string }
+  | { code: 'parse'; file: string; line: number | null; message: string }
+  | { code: 'storage'; message: string }
+  | { code: 'quarantined'; key: string; message: string }
+  | { code: 'outOfScope'; path: string; message: string }
+  | { code: 'repository'; message: string }
+  | { code: 'internal'; message: string };
+
+/** Perfil de aluno neste computador */
+export interface ProfileInfo {
+  id: string;
+  name: string;
+  createdAt: string;
+  lastUsedAt: string | null;
+  hasPin: boolean;
+  active: boolean;
+}
@@ -0,0 +1,500 @@
+// articles.rs - Noun gender, articles and plurals, and the article drill
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use chrono::Utc;
+
+use crate::error::AppError;
+use crate::index::{self, CardRecord};
+use crate::scheduling::{self, CardSchedule, Maturity};
+use crate::text::fold;
+use crate::PreferenceResult;
+
+/// Questions returned when no count is given
+const DEFAULT_DRILL_SIZE: usize = 20;
+
+/// Articles a headword may be written with, and the gender they mark
+const HEADWORD_ARTICLES: &[(&str, Option<Gender>)] = &[
+    ("il ", Some(Gender::Masculine)),
+    ("lo ", Some(Gender::Masculine)),
+    ("i ", Some(Gender::Masculine)),
+    ("gli ", Some(Gender::Masculine)),
+    ("un ", Some(Gender::Masculine)),
+    ("uno ", Some(Gender::Masculine)),
+    ("la ", Some(Gender::Feminine)),
+    ("le ", Some(Gender::Feminine)),
+    ("una ", Some(Gender::Feminine)),
+    ("un'", Some(Gender::Feminine)),
+    ("l'", None),
+];
+
+/// Grammatical gender of a noun
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Gender {
+    Masculine,
+    Feminine,
+}
+
+/// A noun with its articles and plural
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NounForms {
+    pub noun: String,
+    pub gender: Gender,
+    pub plural: String,
+    /// e.g. `lo studente`, `l'amica`
+    pub definite: String,
+    /// e.g. `uno studente`, `un'amica`
+    pub indefinite: String,
+    /// e.g. `gli studenti`, `le amiche`
+    pub plural_definite: String,
+}
+
+/// What an article drill question asks for
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ArticleQuestionKind {
+    Definite,
+    Indefinite,
+    Plural,
+}
+
+impl ArticleQuestionKind {
+    const ALL: [ArticleQuestionKind; 3] = [
+        ArticleQuestionKind::Definite,
+        ArticleQuestionKind::Indefinite,
+        ArticleQuestionKind::Plural,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            ArticleQuestionKind::Definite => "definite",
+            ArticleQuestionKind::Indefinite => "indefinite",
+            ArticleQuestionKind::Plural => "plural",
+        }
+    }
+}
+
+/// A request for the article or plural of a noun
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleQuestion {
+    /// Derived id the noun+kind is scheduled under, e.g. `cibo/pane.md#article:plural`
+    pub drill_id: String,
+    pub card_id: String,
+    pub kind: ArticleQuestionKind,
+    /// `___ studente` for articles, `lo studente` for plurals
+    pub prompt: String,
+    pub english: Vec<String>,
+    pub maturity: Maturity,
+}
+
+/// Outcome of a typed answer
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleResult {
+    pub correct: bool,
+    /// Correct apart from missing or wrong accents; counted as a hard answer
+    pub accent_mistake: bool,
+    pub expected: String,
+    pub schedule: CardSchedule,
+}
+
+/// Reads a `Gender` frontmatter value
+pub(crate) fn parse_gender(value: &str) -> Option<Gender> {
+    match fold(value.trim()).trim_end_matches('.') {
+        "m" | "masc" | "masculine" | "maschile" | "male" => Some(Gender::Masculine),
+        "f" | "fem" | "feminine" | "femminile" | "female" => Some(Gender::Feminine),
+        _ => None,
+    }
+}
+
+/// Splits a leading article off a headword, returning the gender it marks
+fn split_article(headword: &str) -> (Option<Gender>, String) {
+    let headword = headword.trim().replace('’', "'");
+    let lowered = headword.to_lowercase();
+
+    HEADWORD_ARTICLES.iter()
+        .find(|(article, _)| lowered.starts_with(article))
+        .map(|(article, gender)| (*gender, headword[article.len()..].trim().to_string()))
+        .unwrap_or((None, headword))
+}
+
+/// Guesses the gender from the noun ending: -o is masculine; -a, -zione,
+/// -sione, -tà and -tù are feminine; words ending in a consonant are
+/// masculine loanwords. Other endings (such as -e) are ambiguous.
+pub(crate) fn infer_gender(noun: &str) -> Option<Gender> {
+    let folded = fold(noun);
+    let last = noun.chars().last()?;
+
+    if ["zione", "sione"].iter().any(|ending| folded.ends_with(ending))
+        || noun.ends_with("tà")
+        || noun.ends_with("tù")
+    {
+        return Some(Gender::Feminine);
+    }
+
+    match last {
+        'o' => Some(Gender::Masculine),
+        'a' => Some(Gender::Feminine),
+        _ if last.is_alphabetic() && !"aeiouàèéìòù".contains(last) => Some(Gender::Masculine),
+        _ => None,
+    }
+}
+
+/// Whether a word takes `lo`/`gli`/`uno`: s + consonant, z, gn, ps, pn, x and y
+pub(crate) fn takes_lo(word: &str) -> bool {
+    let folded = fold(word);
+    let mut characters = folded.chars();
+
+    match (characters.next(), characters.next()) {
+        (Some('s'), Some(next)) => next.is_alphabetic() && !"aeiou".contains(next),
+        (Some('z' | 'x' | 'y'), _) => true,
+        (Some('g'), Some('n')) | (Some('p'), Some('s' | 'n')) => true,
+        _ => false,
+    }
+}
+
+/// Whether a word starts with a vowel sound (or silent h) and elides the article
+pub(crate) fn elides(word: &str) -> bool {
+    fold(word).chars().next().is_some_and(|first| "aeiouh".contains(first))
+}
+
+/// Regular plural of a noun; invariable for accented endings, consonant
+/// endings (loanwords), -i and -ie. Stress-dependent plurals such as
+/// amico -> amici need a `Plural` field.
+pub(crate) fn plural_of(noun: &str, gender: Gender) -> String {
+    let last = noun.chars().last().unwrap_or_default();
+
+    if !"aeiou".contains(last) || noun.ends_with("ie") {
+        return noun.to_string();
+    }
+
+    let stem = &noun[..noun.len() - 1];
+    let before_ci = stem.chars().rev().nth(2).unwrap_or_default();
+
+    match last {
+        // -ca/-ga keep the hard sound: amica -> amiche, duca -> duchi
+        'a' if stem.ends_with('c') || stem.ends_with('g') => match gender {
+            Gender::Feminine => format!("{}he", stem),
+            Gender::Masculine => format!("{}hi", stem),
+        },
+        // -cia/-gia keep the i only after a vowel: camicia -> camicie, arancia -> arance
+        'a' if gender == Gender::Feminine && (stem.ends_with("ci") || stem.ends_with("gi")) => {
+            match "aeiou".contains(before_ci) {
+                true => format!("{}e", stem),
+                false => format!("{}e", &stem[..stem.len() - 1]),
+            }
+        }
+        'a' => match gender {
+            Gender::Feminine => format!("{}e", stem),
+            Gender::Masculine => format!("{}i", stem),
+        },
+        'o' if stem.ends_with('c') || stem.ends_with('g') => format!("{}hi", stem),
+        // Unstressed -io: negozio -> negozi
+        'o' if stem.ends_with('i') => stem.to_string(),
+        _ => format!("{}i", stem),
+    }
+}
+
+/// Puts an article before a word, without a space after an apostrophe
+fn with_article(article: &str, word: &str) -> String {
+    match article.ends_with('\'') {
+        true => format!("{}{}", article, word),
+        false => format!("{} {}", article, word),
+    }
+}
+
+/// Articles and plural of a noun of known gender
+pub(crate) fn noun_forms(noun: &str, gender: Gender, plural: Option<&str>) -> NounForms {
+    let plural = plural.map(str::to_string).unwrap_or_else(|| plural_of(noun, gender));
+
+    let (definite, indefinite, plural_article) = match gender {
+        Gender::Masculine if takes_lo(noun) => ("lo", "uno", "gli"),
+        Gender::Masculine if elides(noun) => ("l'", "un", "gli"),
+        Gender::Masculine => ("il", "un", if takes_lo(&plural) || elides(&plural) { "gli" } else { "i" }),
+        Gender::Feminine if elides(noun) => ("l'", "un'", "le"),
+        Gender::Feminine => ("la", "una", "le"),
+    };
+
+    NounForms {
+        definite: with_article(definite, noun),
+        indefinite: with_article(indefinite, noun),
+        plural_definite: with_article(plural_article, &plural),
+        noun: noun.to_string(),
+        gender,
+        plural,
+    }
+}
+
+/// Whether a card describes a noun
+pub(crate) fn is_noun(card: &CardRecord) -> bool {
+    match card.part_of_speech.as_deref().map(|part| fold(part.trim())) {
+        Some(part) => matches!(part.as_str(), "noun" | "nome" | "sostantivo" | "n" | "n." | "s"),
+        None => card.gender.is_some(),
+    }
+}
+
+/// Articles and plural of a noun card. The gender comes from the `Gender`
+/// field, then the article written in the headword, then the noun ending.
+pub(crate) fn card_noun_forms(card: &CardRecord) -> PreferenceResult<NounForms> {
+    let (article_gender, noun) = split_article(&card.italian);
+
+    let gender = match card.gender.as_deref() {
+        Some(value) => Some(parse_gender(value)
+            .ok_or_else(|| AppError::invalid("gender", format!("Unknown gender '{}', expected m or f", value)))?),
+        None => article_gender.or_else(|| infer_gender(&noun)),
+    };
+
+    let gender = gender
+        .ok_or_else(|| AppError::invalid("gender", format!("Cannot tell the gender of '{}'; add a Gender field", noun)))?;
+
+    Ok(noun_forms(&noun, gender, card.plural.as_deref()))
+}
+
+/// Derived card id scheduling one kind of article question for a noun
+fn drill_card_id(card_id: &str, kind: ArticleQuestionKind) -> String {
+    format!("{}#article:{}", card_id, kind.key())
+}
+
+/// Lowercases, unifies apostrophes and removes spaces after them
+fn normalize_answer(answer: &str) -> String {
+    answer.trim()
+        .to_lowercase()
+        .replace('’', "'")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("' ", "'")
+}
+
+/// Articles and plural of a noun card
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_noun_forms(
+    app: AppHandle,
+    directory_path: String,
+    card_id: String,
+) -> PreferenceResult<NounForms> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+
+    let card = snapshot.cards.iter()
+        .find(|card| card.id == card_id)
+        .ok_or_else(|| AppError::not_found(format!("Card '{}' does not exist", card_id)))?;
+
+    card_noun_forms(card)
+}
+
+/// Picks article and plural questions: due ones first, then ones never asked
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_article_drill(
+    app: AppHandle,
+    directory_path: String,
+    kinds: Option<Vec<ArticleQuestionKind>>,
+    count: Option<usize>,
+) -> PreferenceResult<Vec<ArticleQuestion>> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+    let schedules = scheduling::load_deck_schedules(&app, &snapshot.directory_path)?;
+    let kinds = kinds.filter(|kinds| !kinds.is_empty()).unwrap_or_else(|| ArticleQuestionKind::ALL.to_vec());
+    let now = Utc::now();
+
+    let mut due = Vec::new();
+    let mut new = Vec::new();
+
+    // Nouns whose gender cannot be determined are skipped
+    for card in snapshot.cards.iter().filter(|card| is_noun(card)) {
+        let Ok(forms) = card_noun_forms(card) else { continue };
+
+        for &kind in &kinds {
+            let drill_id = drill_card_id(&card.id, kind);
+            let schedule = schedules.get(&drill_id);
+
+            let question = ArticleQuestion {
+                prompt: match kind {
+                    ArticleQuestionKind::Plural => forms.definite.clone(),
+                    _ => format!("___ {}", forms.noun),
+                },
+                card_id: card.id.clone(),
+                english: card.english.clone(),
+                maturity: Maturity::of(schedule),
+                drill_id,
+                kind,
+            };
+
+            match schedule {
+                None => new.push(question),
+                Some(schedule) if schedule.due <= now => due.push((schedule.due, question)),
+                Some(_) => {}
+            }
+        }
+    }
+
+    due.sort_by_key(|(due, _)| *due);
+    new.shuffle(&mut rand::thread_rng());
+
+    Ok(due.into_iter()
+        .map(|(_, question)| question)
+        .chain(new)
+        .take(count.unwrap_or(DEFAULT_DRILL_SIZE))
+        .collect())
+}
+
+/// Checks an answer and records it. Article questions accept the article
+/// alone or with the noun; plural questions expect article and plural.
+#[tauri::command(rename_all = "camelCase")]
+pub fn answer_article_drill(
+    app: AppHandle,
+    directory_path: String,
+    card_id: String,
+    kind: ArticleQuestionKind,
+    answer: String,
+) -> PreferenceResult<ArticleResult> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+    let card = snapshot.cards.iter()
+        .find(|card| card.id == card_id)
+        .ok_or_else(|| AppError::not_found(format!("Card '{}' does not exist", card_id)))?;
+
+    let forms = card_noun_forms(card)?;
+    let expected = match kind {
+        ArticleQuestionKind::Definite => forms.definite.clone(),
+        ArticleQuestionKind::Indefinite => forms.indefinite.clone(),
+        ArticleQuestionKind::Plural => forms.plural_definite.clone(),
+    };
+
+    let mut accepted = vec![normalize_answer(&expected)];
+    if kind != ArticleQuestionKind::Plural {
+        let article = accepted[0].strip_suffix(&fold(&forms.noun))
+            .or_else(|| accepted[0].strip_suffix(&forms.noun.to_lowercase()))
+            .map(|article| article.trim().to_string());
+        accepted.extend(article);
+    }
+
+    let answer = normalize_answer(&answer);
+    let correct = accepted.contains(&answer);
+    let accent_mistake = !correct && accepted.iter().any(|form| fold(form) == fold(&answer));
+
+    let grade = match (correct, accent_mistake) {
+        (true, _) => 2,
+        (false, true) => 1,
+        (false, false) => 0,
+    };
+
+    let schedule = scheduling::record_review(&app, &snapshot.directory_path, &drill_card_id(&card_id, kind), grade)?;
+
+    Ok(ArticleResult {
+        correct,
+        accent_mistake,
+        expected,
+        schedule,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forms(noun: &str, gender: Gender) -> NounForms {
+        noun_forms(noun, gender, None)
+    }
+
+    #[test]
+    fn lo_and_gli_before_s_consonant_z_gn_ps() {
+        for (noun, definite, indefinite, plural) in [
+            ("studente", "lo studente", "uno studente", "gli studenti"),
+            ("sbaglio", "lo sbaglio", "uno sbaglio", "gli sbagli"),
+            ("zaino", "lo zaino", "uno zaino", "gli zaini"),
+            ("gnomo", "lo gnomo", "uno gnomo", "gli gnomi"),
+            ("pseudonimo", "lo pseudonimo", "uno pseudonimo", "gli pseudonimi"),
+            ("xilofono", "lo xilofono", "uno xilofono", "gli xilofoni"),
+            ("yogurt", "lo yogurt", "uno yogurt", "gli yogurt"),
+        ] {
+            let forms = forms(noun, Gender::Masculine);
+            assert_eq!(forms.definite, definite);
+            assert_eq!(forms.indefinite, indefinite);
+            assert_eq!(forms.plural_definite, plural);
+        }
+
+        assert_eq!(forms("pneumatico", Gender::Masculine).definite, "lo pneumatico");
+    }
+
+    #[test]
+    fn s_before_vowel_takes_il() {
+        let forms = forms("sale", Gender::Masculine);
+        assert_eq!(forms.definite, "il sale");
+        assert_eq!(forms.indefinite, "un sale");
+        assert_eq!(forms.plural_definite, "i sali");
+    }
+
+    #[test]
+    fn vowels_elide_the_article() {
+        let masculine = forms("albero", Gender::Masculine);
+        assert_eq!(masculine.definite, "l'albero");
+        assert_eq!(masculine.indefinite, "un albero");
+        assert_eq!(masculine.plural_definite, "gli alberi");
+
+        let feminine = forms("amica", Gender::Feminine);
+        assert_eq!(feminine.definite, "l'amica");
+        assert_eq!(feminine.indefinite, "un'amica");
+        assert_eq!(feminine.plural_definite, "le amiche");
+
+        let silent_h = forms("hotel", Gender::Masculine);
+        assert_eq!(silent_h.definite, "l'hotel");
+        assert_eq!(silent_h.plural_definite, "gli hotel");
+    }
+
+    #[test]
+    fn consonants_take_il_and_la() {
+        assert_eq!(forms("libro", Gender::Masculine).definite, "il libro");
+        assert_eq!(forms("libro", Gender::Masculine).plural_definite, "i libri");
+        assert_eq!(forms("casa", Gender::Feminine).definite, "la casa");
+        assert_eq!(forms("casa", Gender::Feminine).indefinite, "una casa");
+        assert_eq!(forms("casa", Gender::Feminine).plural_definite, "le case");
+    }
+
+    #[test]
+    fn regular_plurals() {
+        assert_eq!(plural_of("cane", Gender::Masculine), "cani");
+        assert_eq!(plural_of("chiave", Gender::Feminine), "chiavi");
+        assert_eq!(plural_of("problema", Gender::Masculine), "problemi");
+        assert_eq!(plural_of("lago", Gender::Masculine), "laghi");
+        assert_eq!(plural_of("negozio", Gender::Masculine), "negozi");
+        assert_eq!(plural_of("camicia", Gender::Feminine), "camicie");
+        assert_eq!(plural_of("arancia", Gender::Feminine), "arance");
+        assert_eq!(plural_of("città", Gender::Feminine), "città");
+        assert_eq!(plural_of("bar", Gender::Masculine), "bar");
+        assert_eq!(plural_of("crisi", Gender::Feminine), "crisi");
+        assert_eq!(plural_of("serie", Gender::Feminine), "serie");
+    }
+
+    #[test]
+    fn irregular_plural_overrides_the_rules() {
+        let forms = noun_forms("uomo", Gender::Masculine, Some("uomini"));
+        assert_eq!(forms.definite, "l'uomo");
+        assert_eq!(forms.plural_definite, "gli uomini");
+    }
+
+    #[test]
+    fn gender_from_article_field_or_ending() {
+        assert_eq!(split_article("il cane"), (Some(Gender::Masculine), "cane".to_string()));
+        assert_eq!(split_article("La chiave"), (Some(Gender::Feminine), "chiave".to_string()));
+        assert_eq!(split_article("un’amica"), (Some(Gender::Feminine), "amica".to_string()));
+        assert_eq!(split_article("l'acqua"), (None, "acqua".to_string()));
+
+        assert_eq!(infer_gender("libro"), Some(Gender::Masculine));
+        assert_eq!(infer_gender("casa"), Some(Gender::Feminine));
+        assert_eq!(infer_gender("stazione"), Some(Gender::Feminine));
+        assert_eq!(infer_gender("città"), Some(Gender::Feminine));
+        assert_eq!(infer_gender("sport"), Some(Gender::Masculine));
+        assert_eq!(infer_gender("cane"), None);
+
+        assert_eq!(parse_gender("M."), Some(Gender::Masculine));
+        assert_eq!(parse_gender("femminile"), Some(Gender::Feminine));
+        assert_eq!(parse_gender("neuter"), None);
+    }
+
+    #[test]
+    fn answers_ignore_apostrophe_style_and_spacing() {
+        assert_eq!(normalize_answer(" L’ amica "), "l'amica");
+        assert_eq!(normalize_answer("gli   studenti"), "gli studenti");
+    }
+}
@@ -0,0 +1,384 @@
+// authoring.rs - Creating, editing and deleting vocabulary notes
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value as YamlValue};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::index::{self, card_id, CardRecord};
+use crate::scope::{self, ScopeError};
+use crate::{split_frontmatter, PreferenceResult};
+
+/// Frontmatter keys written by the app, with the aliases accepted when reading
+const ITALIAN_KEYS: &[&str] = &["Italian"];
+const ENGLISH_KEYS: &[&str] = &["English"];
+const TAGS_KEYS: &[&str] = &["Tags", "tags"];
+const PART_OF_SPEECH_KEYS: &[&str] = &["PartOfSpeech", "partOfSpeech", "pos"];
+const GENDER_KEYS: &[&str] = &["Gender", "gender"];
+
+/// Characters that are not allowed in file names on at least one platform
+const FORBIDDEN_FILE_NAME_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// File names reserved by Windows regardless of extension
+const RESERVED_FILE_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Card fields sent by the editor
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardDraft {
+    pub(crate) italian: Vec<String>,
+    pub(crate) english: Vec<String>,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    pub(crate) part_of_speech: Option<String>,
+    pub(crate) gender: Option<String>,
+    /// Markdown body; `None` keeps the existing body when editing
+    pub(crate) body: Option<String>,
+}
+
+impl CardDraft {
+    /// Draft holding the current fields of a card, leaving its body untouched
+    pub(crate) fn from_record(card: &CardRecord) -> Self {
+        Self {
+            italian: card.italian_forms.clone(),
+            english: card.english.clone(),
+            tags: card.tags.clone(),
+            part_of_speech: card.part_of_speech.clone(),
+            gender: card.gender.clone(),
+            body: None,
+        }
+    }
+
+    /// Trims every field and drops empty entries, failing if a required field is empty
+    fn normalized(self) -> PreferenceResult<Self> {
+        let clean_list = |values: Vec<String>| -> Vec<String> {
+            values.into_iter()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect()
+        };
+        let clean_option = |value: Option<String>| {
+            value.map(|text| text.trim().to_string()).filter(|text| !text.is_empty())
+        };
+
+        let draft = Self {
+            italian: clean_list(self.italian),
+            english: clean_list(self.english),
+            tags: clean_list(self.tags),
+            part_of_speech: clean_option(self.part_of_speech),
+            gender: clean_option(self.gender),
+            body: self.body,
+        };
+
+        if draft.italian.is_empty() {
+            return Err(AppError::invalid("italian", "Italian field cannot be empty"));
+        }
+
+        if draft.english.is_empty() {
+            return Err(AppError::invalid("english", "English field cannot be empty"));
+        }
+
+        Ok(draft)
+    }
+}
+
+/// Builds a file name from the Italian headword that is valid on every platform
+pub(crate) fn safe_file_name(headword: &str) -> PreferenceResult<String> {
+    let cleaned: String = headword
+        .chars()
+        .map(|character| match character {
+            _ if character.is_control() => ' ',
+            _ if FORBIDDEN_FILE_NAME_CHARS.contains(&character) => '-',
+            _ => character,
+        })
+        .collect();
+
+    // Collapse whitespace and strip characters Windows silently drops at the end
+    let mut stem = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    stem = stem.trim_matches(|character: char| character == '.' || character == ' ').to_string();
+
+    if stem.is_empty() || stem.chars().all(|character| character == '-') {
+        return Err(AppError::invalid("italian", format!("Cannot build a file name from '{}'", headword)));
+    }
+
+    if RESERVED_FILE_NAMES.contains(&stem.to_uppercase().as_str()) {
+        stem.push('_');
+    }
+
+    Ok(format!("{}.md", stem))
+}
+
+/// Resolves a card id (or folder) relative to the deck root, rejecting absolute paths, `..`
+/// and symlinks that lead out of the deck. The returned path is canonical as far as it exists.
+pub(crate) fn resolve_in_deck(root: &Path, relative_path: &str) -> PreferenceResult<PathBuf> {
+    let relative = Path::new(relative_path);
+
+    let is_plain = relative.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+    if !is_plain {
+        return Err(AppError::OutOfScope {
+            path: relative_path.to_string(),
+            message: format!("Path '{}' must stay inside the deck", relative_path),
+        });
+    }
+
+    let escape = || AppError::from(ScopeError::Escape(root.join(relative)));
+    let root = fs::canonicalize(root)
+        .map_err(|e| AppError::io(root, format!("Failed to resolve deck folder '{}': {}", root.display(), e)))?;
+
+    // Resolve the deepest existing ancestor, so a symlink anywhere on the way is followed,
+    // then append the parts that do not exist yet (e.g. a new card or folder)
+    let joined = root.join(relative);
+    let mut existing = joined.as_path();
+    let mut missing = Vec::new();
+
+    let mut resolved = loop {
+        match fs::canonicalize(existing) {
+            Ok(canonical) => break canonical,
+            // A dangling symlink cannot be resolved and would be followed when written to
+            Err(_) if fs::symlink_metadata(existing).is_ok() => return Err(escape()),
+            Err(_) => {
+                let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+                    return Err(escape());
+                };
+                missing.push(name);
+                existing = parent;
+            }
+        }
+    };
+
+    resolved.extend(missing.into_iter().rev());
+
+    if !resolved.starts_with(&root) {
+        return Err(escape());
+    }
+
+    Ok(resolved)
+}
+
+/// Finds the key used for a field in an existing frontmatter, if any
+fn existing_key(mapping: &Mapping, keys: &[&str]) -> Option<YamlValue> {
+    keys.iter()
+        .map(|key| YamlValue::String(key.to_string()))
+        .find(|key| mapping.contains_key(key))
+}
+
+/// Sets a field in place (keeping its position and spelling) or appends it,
+/// removing it when there is no value
+fn set_field(mapping: &mut Mapping, keys: &[&str], value: Option<YamlValue>) {
+    let key = existing_key(mapping, keys)
+        .unwrap_or_else(|| YamlValue::String(keys[0].to_string()));
+
+    match value {
+        Some(value) => {
+            mapping.insert(key, value);
+        }
+        None => {
+            mapping.shift_remove(&key);
+        }
+    }
+}
+
+/// A list value, written as a plain string when it has a single entry and the
+/// field was not already a list
+fn list_value(values: &[String], previous: Option<&YamlValue>) -> YamlValue {
+    let keep_list = values.len() > 1 || matches!(previous, Some(YamlValue::Sequence(_)));
+
+    match keep_list {
+        true => YamlValue::Sequence(values.iter().cloned().map(YamlValue::String).collect()),
+        false => YamlValue::String(values[0].clone()),
+    }
+}
+
+/// Applies a draft to a frontmatter mapping without disturbing unknown fields
+fn apply_draft(mapping: &mut Mapping, draft: &CardDraft) {
+    let previous_italian = existing_key(mapping, ITALIAN_KEYS).and_then(|key| mapping.get(&key).cloned());
+
+    set_field(mapping, ITALIAN_KEYS, Some(list_value(&draft.italian, previous_italian.as_ref())));
+    set_field(mapping, ENGLISH_KEYS, Some(YamlValue::Sequence(
+        draft.english.iter().cloned().map(YamlValue::String).collect(),
+    )));
+    set_field(mapping, TAGS_KEYS, (!draft.tags.is_empty()).then(|| YamlValue::Sequence(
+        draft.tags.iter().cloned().map(YamlValue::String).collect(),
+    )));
+    set_field(mapping, PART_OF_SPEECH_KEYS, draft.part_of_speech.clone().map(YamlValue::String));
+    set_field(mapping, GENDER_KEYS, draft.gender.clone().map(YamlValue::String));
+}
+
+/// Renders a note from the text before its frontmatter, the frontmatter and the body
+fn render_note(preamble: &str, mapping: &Mapping, body: &str) -> PreferenceResult<String> {
+    let yaml = serde_yaml::to_string(mapping)
+        .map_err(|e| AppError::internal(format!("Failed to serialize YAML frontmatter: {}", e)))?;
+
+    Ok(format!("{}---\n{}---{}", preamble, yaml, body))
+}
+
+/// Body text as stored after the closing `---`
+fn stored_body(body: &str) -> String {
+    match body.trim().is_empty() {
+        true => "\n".to_string(),
+        false => format!("\n\n{}\n", body.trim()),
+    }
+}
+
+/// Resolves an existing card file inside a deck
+pub(crate) fn existing_card_path(app: &AppHandle, directory_path: &str, card_id: &str) -> PreferenceResult<(PathBuf, PathBuf)> {
+    let root = scope::scoped_deck_path(app, directory_path)?;
+    let path = resolve_in_deck(&root, card_id)?;
+
+    if !path.is_file() {
+        return Err(AppError::not_found(format!("Card '{}' does not exist", card_id)));
+    }
+
+    Ok((root, path))
+}
+
+/// Creates a new note named after the Italian headword. Never overwrites an existing file.
+#[tauri::command(rename_all = "camelCase")]
+pub fn create_card(
+    app: AppHandle,
+    directory_path: String,
+    folder: Option<String>,
+    card: CardDraft,
+) -> PreferenceResult<CardRecord> {
+    let card = card.normalized()?;
+    let root = scope::scoped_deck_path(&app, &directory_path)?;
+
+    let target_folder = match folder.as_deref().map(str::trim).filter(|folder| !folder.is_empty()) {
+        Some(folder) => resolve_in_deck(&root, folder)?,
+        None => root.clone(),
+    };
+
+    fs::create_dir_all(&target_folder)
+        .map_err(|e| AppError::io(&target_folder, format!("Failed to create folder '{}': {}", target_folder.display(), e)))?;
+
+    let path = target_folder.join(safe_file_name(&card.italian[0])?);
+
+    let mut mapping = Mapping::new();
+    apply_draft(&mut mapping, &card);
+    let content = render_note("", &mapping, &stored_body(card.body.as_deref().unwrap_or_default()))?;
+
+    // create_new fails atomically if the file already exists
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => {
+                AppError::already_exists(&path, format!("A card named '{}' already exists", path.display()))
+            }
+            _ => AppError::io(&path, format!("Failed to create card '{}': {}", path.display(), e)),
+        })?;
+
+    file.write_all(content.as_bytes())
+        .map_err(|e| AppError::io(&path, format!("Failed to write card '{}': {}", path.display(), e)))?;
+
+    index::parse_card(&card_id(&root, &path), &path, &content)
+}
+
+/// Rewrites the frontmatter of an existing note file in place
+pub(crate) fn rewrite_card(root: &Path, path: &Path, card: CardDraft) -> PreferenceResult<CardRecord> {
+    let card = card.normalized()?;
+    let card_id = card_id(root, path);
+
+    let file_content = fs::read_to_string(path)
+        .map_err(|e| AppError::io(path, format!("Failed to read card '{}': {}", card_id, e)))?;
+    let (yaml_frontmatter, body) = split_frontmatter(&file_content)
+        .map_err(|error| error.in_file(path))?;
+
+    // Text before the opening `---` (e.g. a comment or BOM) is written back unchanged
+    let preamble = file_content.split("---").next().unwrap_or_default();
+
+    let mut mapping: Mapping = match yaml_frontmatter.trim().is_empty() {
+        true => Mapping::new(),
+        false => serde_yaml::from_str(yaml_frontmatter)
+            .map_err(|e| AppError::yaml(path, &e))?,
+    };
+
+    apply_draft(&mut mapping, &card);
+
+    let body = match &card.body {
+        Some(new_body) => stored_body(new_body),
+        None => body.to_string(),
+    };
+    let content = render_note(preamble, &mapping, &body)?;
+
+    fs::write(path, &content)
+        .map_err(|e| AppError::io(path, format!("Failed to write card '{}': {}", card_id, e)))?;
+
+    index::parse_card(&card_id, path, &content)
+}
+
+/// Updates a note's frontmatter (and optionally body), keeping unknown fields and key order
+#[tauri::command(rename_all = "camelCase")]
+pub fn update_card(
+    app: AppHandle,
+    directory_path: String,
+    card_id: String,
+    card: CardDraft,
+) -> PreferenceResult<CardRecord> {
+    let (root, path) = existing_card_path(&app, &directory_path, &card_id)?;
+
+    rewrite_card(&root, &path, card)
+}
+
+/// Deletes a note from the deck
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_card(app: AppHandle, directory_path: String, card_id: String) -> PreferenceResult<()> {
+    let (_, path) = existing_card_path(&app, &directory_path, &card_id)?;
+
+    fs::remove_file(&path)
+        .map_err(|e| AppError::io(&path, format!("Failed to delete card '{}': {}", card_id, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn resolves_new_and_existing_paths_inside_the_deck() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("animali")).unwrap();
+        fs::write(root.join("animali/cane.md"), "").unwrap();
+
+        assert_eq!(resolve_in_deck(&root, "animali/cane.md").unwrap(), root.join("animali/cane.md"));
+        assert_eq!(resolve_in_deck(&root, "./animali/nuovi/gatto.md").unwrap(), root.join("animali/nuovi/gatto.md"));
+        assert!(resolve_in_deck(&root, "../cane.md").is_err());
+        assert!(resolve_in_deck(&root, &root.join("animali").to_string_lossy()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_leading_out_of_the_deck() {
+        use std::os::unix::fs::symlink;
+
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("deck");
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(root.join("verbi")).unwrap();
+        fs::create_dir(&outside).unwrap();
+        fs::write(outside.join("secret.md"), "").unwrap();
+
+        symlink(&outside, root.join("fuori")).unwrap();
+        symlink(outside.join("secret.md"), root.join("secret.md")).unwrap();
+        symlink(outside.join("missing.md"), root.join("dangling.md")).unwrap();
+        symlink(root.join("verbi"), root.join("alias")).unwrap();
+
+        for relative_path in ["fuori/secret.md", "fuori/nuova/carta.md", "fuori", "secret.md", "dangling.md"] {
+            let error = resolve_in_deck(&root, relative_path).unwrap_err();
+            assert!(matches!(error, AppError::OutOfScope { .. }), "{}: {:?}", relative_path, error);
+        }
+
+        // Symlinks that stay inside the deck keep working
+        let root = root.canonicalize().unwrap();
+        assert_eq!(resolve_in_deck(&root, "alias/andare.md").unwrap(), root.join("verbi/andare.md"));
+    }
+}
@@ -0,0 +1,314 @@
+// cloze.rs - Cloze deletion cards derived from note bodies
+use std::collections::BTreeSet;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::index::{self, CardRecord};
+use crate::scheduling::{self, CardSchedule, Maturity};
+use crate::text::{fold, normalize_headword};
+use crate::PreferenceResult;
+
+/// Shown in place of a hidden answer that has no hint
+const GAP: &str = "[...]";
+
+/// Separates the note id from the cloze suffix in derived card ids
+const CLOZE_ID_SEPARATOR: char = '#';
+
+/// A piece of an example line: plain text or a deletion
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Cloze {
+        number: u32,
+        answer: &'a str,
+        hint: Option<&'a str>,
+    },
+}
+
+/// A card asking for the deletions of one cloze number
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClozeCard {
+    /// Derived id used for scheduling, e.g. `verbi/andare.md#c1`
+    pub card_id: String,
+    pub note_id: String,
+    /// Text with this cloze's answers hidden and other deletions shown
+    pub masked: String,
+    /// Text with every answer shown
+    pub revealed: String,
+    pub answers: Vec<String>,
+    /// True when generated from the headword rather than from `{{c1::...}}` markup
+    pub automatic: bool,
+}
+
+/// A cloze card with its review state
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledClozeCard {
+    #[serde(flatten)]
+    pub cloze: ClozeCard,
+    pub schedule: Option<CardSchedule>,
+    pub maturity: Maturity,
+}
+
+/// Splits a line into text and `{{cN::answer}}` / `{{cN::answer::hint}}` deletions.
+/// Malformed markup is kept as plain text.
+fn parse_segments(line: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find("{{c") {
+        let after_marker = &rest[start + 3..];
+        let digits_len = after_marker.find(|character: char| !character.is_ascii_digit())
+            .unwrap_or(after_marker.len());
+
+        let parsed = after_marker[..digits_len].parse::<u32>().ok()
+            .filter(|_| after_marker[digits_len..].starts_with("::"))
+            .and_then(|number| {
+                let content_start = digits_len + 2;
+                let content_len = after_marker[content_start..].find("}}")?;
+                let content = &after_marker[content_start..content_start + content_len];
+
+                let (answer, hint) = match content.split_once("::") {
+                    Some((answer, hint)) => (answer, Some(hint.trim()).filter(|hint| !hint.is_empty())),
+                    None => (content, None),
+                };
+
+                let consumed = 3 + content_start + content_len + 2;
+                Some((Segment::Cloze { number, answer, hint }, consumed))
+            });
+
+        match parsed {
+            Some((segment, consumed)) if !matches!(segment, Segment::Cloze { answer: "", .. }) => {
+                if start > 0 {
+                    segments.push(Segment::Text(&rest[..start]));
+                }
+                segments.push(segment);
+                rest = &rest[start + consumed..];
+            }
+            _ => {
+                segments.push(Segment::Text(&rest[..start + 3]));
+                rest = &rest[start + 3..];
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+
+    segments
+}
+
+/// Renders segments, hiding the deletions of `hidden` (all shown when `None`)
+fn render_segments(segments: &[Segment<'_>], hidden: Option<u32>) -> String {
+    segments.iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.to_string(),
+            Segment::Cloze { number, hint, .. } if Some(*number) == hidden => match hint {
+                Some(hint) => format!("[{}]", hint),
+                None => GAP.to_string(),
+            },
+            Segment::Cloze { answer, .. } => answer.to_string(),
+        })
+        .collect()
+}
+
+/// Removes cloze markup from text, keeping the answers
+pub(crate) fn reveal(text: &str) -> String {
+    render_segments(&parse_segments(text), None)
+}
+
+/// Lines of a note body that can hold example sentences, without list or quote markers
+fn example_lines(body: &str) -> Vec<&str> {
+    let mut in_code_block = false;
+
+    body.lines()
+        .filter(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+                return false;
+            }
+            !in_code_block
+        })
+        .map(|line| line.trim().trim_start_matches(['-', '*', '+', '>']).trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Byte ranges of the words of a line
+fn word_spans(line: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+
+    for (position, character) in line.char_indices() {
+        match (character.is_alphanumeric(), start) {
+            (true, None) => start = Some(position),
+            (false, Some(word_start)) => {
+                spans.push((word_start, position));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(word_start) = start {
+        spans.push((word_start, line.len()));
+    }
+
+    spans
+}
+
+/// Segments hiding every occurrence of a headword form in a line, if it appears
+fn headword_segments<'a>(line: &'a str, forms: &[Vec<String>]) -> Option<Vec<Segment<'a>>> {
+    let spans = word_spans(line);
+    let words: Vec<String> = spans.iter().map(|&(start, end)| fold(&line[start..end])).collect();
+
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut position = 0;
+
+    while position < words.len() {
+        let matched = forms.iter()
+            .filter(|form| !form.is_empty() && position + form.len() <= words.len())
+            .find(|form| words[position..position + form.len()] == form[..]);
+
+        match matched {
+            Some(form) => {
+                let start = spans[position].0;
+                let end = spans[position + form.len() - 1].1;
+
+                if start > text_start {
+                    segments.push(Segment::Text(&line[text_start..start]));
+                }
+                segments.push(Segment::Cloze { number: 1, answer: &line[start..end], hint: None });
+
+                text_start = end;
+                position += form.len();
+            }
+            None => position += 1,
+        }
+    }
+
+    if segments.is_empty() {
+        return None;
+    }
+
+    if text_start < line.len() {
+        segments.push(Segment::Text(&line[text_start..]));
+    }
+
+    Some(segments)
+}
+
+/// Derived card id of a cloze
+fn cloze_card_id(note_id: &str, suffix: &str) -> String {
+    format!("{}{}{}", note_id, CLOZE_ID_SEPARATOR, suffix)
+}
+
+/// Builds the cloze cards of a note. Explicit `{{cN::...}}` markup gives one
+/// card per number; without markup, every example line containing the
+/// headword becomes its own card.
+pub(crate) fn cloze_cards(card: &CardRecord) -> Vec<ClozeCard> {
+    let lines: Vec<Vec<Segment<'_>>> = example_lines(&card.body)
+        .into_iter()
+        .map(parse_segments)
+        .collect();
+
+    let numbers: BTreeSet<u32> = lines.iter()
+        .flatten()
+        .filter_map(|segment| match segment {
+            Segment::Cloze { number, .. } => Some(*number),
+            Segment::Text(_) => None,
+        })
+        .collect();
+
+    if !numbers.is_empty() {
+        return numbers.into_iter()
+            .map(|number| {
+                let cloze_lines: Vec<&Vec<Segment<'_>>> = lines.iter()
+                    .filter(|segments| segments.iter().any(|segment| matches!(segment, Segment::Cloze { number: n, .. } if *n == number)))
+                    .collect();
+
+                let answers = cloze_lines.iter()
+                    .flat_map(|segments| segments.iter())
+                    .filter_map(|segment| match segment {
+                        Segment::Cloze { number: n, answer, .. } if *n == number => Some(answer.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+
+                let render = |hidden| cloze_lines.iter()
+                    .map(|segments| render_segments(segments, hidden))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                ClozeCard {
+                    card_id: cloze_card_id(&card.id, &format!("c{}", number)),
+                    note_id: card.id.clone(),
+                    masked: render(Some(number)),
+                    revealed: render(None),
+                    answers,
+                    automatic: false,
+                }
+            })
+            .collect();
+    }
+
+    // Word sequences of every Italian form, without the leading article
+    let forms: Vec<Vec<String>> = card.italian_forms.iter()
+        .map(|form| normalize_headword(form).split_whitespace().map(str::to_string).collect())
+        .collect();
+
+    example_lines(&card.body)
+        .into_iter()
+        .filter_map(|line| headword_segments(line, &forms))
+        .enumerate()
+        .map(|(position, segments)| ClozeCard {
+            card_id: cloze_card_id(&card.id, &format!("auto{}", position + 1)),
+            note_id: card.id.clone(),
+            masked: render_segments(&segments, Some(1)),
+            revealed: render_segments(&segments, None),
+            answers: segments.iter()
+                .filter_map(|segment| match segment {
+                    Segment::Cloze { answer, .. } => Some(answer.to_string()),
+                    Segment::Text(_) => None,
+                })
+                .collect(),
+            automatic: true,
+        })
+        .collect()
+}
+
+/// Lists the cloze cards of a deck, or of a single note, with their schedules
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_cloze_cards(
+    app: AppHandle,
+    directory_path: String,
+    card_id: Option<String>,
+) -> PreferenceResult<Vec<ScheduledClozeCard>> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+    let schedules = scheduling::load_deck_schedules(&app, &snapshot.directory_path)?;
+
+    if let Some(card_id) = &card_id {
+        if !snapshot.cards.iter().any(|card| &card.id == card_id) {
+            return Err(AppError::not_found(format!("Card '{}' does not exist", card_id)));
+        }
+    }
+
+    let clozes = snapshot.cards.iter()
+        .filter(|card| card_id.as_ref().map_or(true, |card_id| &card.id == card_id))
+        .flat_map(cloze_cards)
+        .map(|cloze| {
+            let schedule = schedules.get(&cloze.card_id).cloned();
+            ScheduledClozeCard {
+                maturity: Maturity::of(schedule.as_ref()),
+                schedule,
+                cloze,
+            }
+        })
+        .collect();
+
+    Ok(clozes)
+}
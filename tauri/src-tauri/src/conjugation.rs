@@ -0,0 +1,522 @@
+// conjugation.rs - Conjugation tables of regular Italian verbs
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::index::{self, CardRecord};
+use crate::text::{fold, normalize_headword};
+use crate::PreferenceResult;
+
+/// Grammatical persons, in the order forms are listed
+pub(crate) const PERSONS: [&str; 6] = ["io", "tu", "lui/lei", "noi", "voi", "loro"];
+
+/// Present tense of the two auxiliaries
+const AVERE_PRESENT: [&str; 6] = ["ho", "hai", "ha", "abbiamo", "avete", "hanno"];
+const ESSERE_PRESENT: [&str; 6] = ["sono", "sei", "è", "siamo", "siete", "sono"];
+
+/// Reflexive pronouns placed before every form of a reflexive verb
+const REFLEXIVE_PRONOUNS: [&str; 6] = ["mi", "ti", "si", "ci", "vi", "si"];
+
+/// Common -ire verbs conjugated without -isc-; every other -ire verb takes it
+const NON_ISC_VERBS: &[&str] = &[
+    "aprire", "avvertire", "bollire", "consentire", "convertire", "coprire", "divertire",
+    "dormire", "fuggire", "investire", "mentire", "offrire", "partire", "pentire", "riaprire",
+    "ripartire", "scoprire", "seguire", "sentire", "servire", "soffrire", "vestire",
+];
+
+/// Tenses generated for verbs
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum Tense {
+    Presente,
+    PassatoProssimo,
+    Imperfetto,
+    Futuro,
+    Condizionale,
+    Congiuntivo,
+}
+
+impl Tense {
+    pub(crate) const ALL: [Tense; 6] = [
+        Tense::Presente,
+        Tense::PassatoProssimo,
+        Tense::Imperfetto,
+        Tense::Futuro,
+        Tense::Condizionale,
+        Tense::Congiuntivo,
+    ];
+
+    /// Name used in frontmatter overrides and derived card ids
+    pub(crate) fn key(self) -> &'static str {
+        match self {
+            Tense::Presente => "presente",
+            Tense::PassatoProssimo => "passatoProssimo",
+            Tense::Imperfetto => "imperfetto",
+            Tense::Futuro => "futuro",
+            Tense::Condizionale => "condizionale",
+            Tense::Congiuntivo => "congiuntivo",
+        }
+    }
+
+    /// Name shown to the learner
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Tense::PassatoProssimo => "passato prossimo",
+            tense => tense.key(),
+        }
+    }
+}
+
+/// Irregular forms given in a note's `Conjugation` frontmatter field:
+///
+/// ```yaml
+/// Conjugation:
+///   participio: andato
+///   presente: [vado, vai, va, ~, ~, vanno]
+///   futuro: [andrò, andrai, andrà, andremo, andrete, andranno]
+/// ```
+///
+/// `~` keeps the generated form for that person. Unknown tenses and malformed
+/// form lists are skipped and listed in `ignored`, so a typo never drops the card.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", from = "RawConjugationOverrides")]
+pub struct ConjugationOverrides {
+    pub participio: Option<String>,
+    /// Forces or disables the -isc- infix of -ire verbs
+    pub isc: Option<bool>,
+    #[serde(flatten)]
+    pub tenses: BTreeMap<Tense, Vec<Option<String>>>,
+    /// Why entries of the `Conjugation` field were skipped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignored: Vec<String>,
+}
+
+/// `Conjugation` field as written, before its tense keys are checked
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawConjugationOverrides {
+    #[serde(default)]
+    participio: Option<String>,
+    #[serde(default)]
+    isc: Option<bool>,
+    #[serde(default)]
+    ignored: Vec<String>,
+    #[serde(flatten)]
+    tenses: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl From<RawConjugationOverrides> for ConjugationOverrides {
+    fn from(raw: RawConjugationOverrides) -> Self {
+        let mut ignored = raw.ignored;
+        let mut tenses = BTreeMap::new();
+
+        for (key, value) in raw.tenses {
+            let Some(tense) = Tense::ALL.into_iter().find(|tense| tense.key() == key) else {
+                ignored.push(format!("Unknown tense '{}' was ignored", key));
+                continue;
+            };
+
+            match serde_yaml::from_value(value) {
+                Ok(forms) => {
+                    tenses.insert(tense, forms);
+                }
+                Err(e) => ignored.push(format!("Forms of {} were ignored: {}", key, e)),
+            }
+        }
+
+        ConjugationOverrides {
+            participio: raw.participio,
+            isc: raw.isc,
+            tenses,
+            ignored,
+        }
+    }
+}
+
+/// Auxiliary verb of compound tenses
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Auxiliary {
+    Avere,
+    Essere,
+}
+
+/// Every generated form of a verb
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConjugationTable {
+    pub infinitive: String,
+    pub auxiliary: Auxiliary,
+    pub participle: String,
+    pub persons: [&'static str; 6],
+    /// Six forms per tense, in the order of `persons`. Forms agreeing in
+    /// gender list the alternatives after a slash, e.g. `sono andato/a`.
+    pub tenses: BTreeMap<Tense, Vec<String>>,
+    /// Overrides skipped because they could not be read
+    pub warnings: Vec<String>,
+}
+
+/// Conjugation class, given by the infinitive ending
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Are,
+    Ere,
+    Ire,
+}
+
+impl Class {
+    /// Thematic vowel that forms the imperfetto stem
+    fn vowel(self) -> &'static str {
+        match self {
+            Class::Are => "a",
+            Class::Ere => "e",
+            Class::Ire => "i",
+        }
+    }
+}
+
+/// Whether a card's part of speech marks it as a verb
+pub(crate) fn is_verb(card: &CardRecord) -> bool {
+    card.part_of_speech.as_deref()
+        .map(|part| fold(part.trim()))
+        .is_some_and(|part| matches!(part.as_str(), "verb" | "verbo" | "v" | "v."))
+}
+
+/// Appends an ending to a stem, applying the spelling rules of regular verbs:
+/// `cerc` + `i` -> `cerchi`, `mangi` + `iamo` -> `mangiamo`
+fn join(stem: &str, ending: &str, class: Class) -> String {
+    if stem.ends_with('i') && ending.starts_with('i') {
+        return format!("{}{}", stem, &ending[1..]);
+    }
+
+    if class == Class::Are
+        && (stem.ends_with('c') || stem.ends_with('g'))
+        && (ending.starts_with('i') || ending.starts_with('e'))
+    {
+        return format!("{}h{}", stem, ending);
+    }
+
+    format!("{}{}", stem, ending)
+}
+
+/// Applies endings to a stem for all six persons
+fn with_endings(stem: &str, endings: [&str; 6], class: Class) -> Vec<String> {
+    endings.iter().map(|ending| join(stem, ending, class)).collect()
+}
+
+/// Adds the gender alternatives of a participle used with essere
+fn agreeing_participle(participle: &str, plural: bool) -> String {
+    match participle.strip_suffix('o') {
+        Some(base) if plural => format!("{}i/e", base),
+        Some(base) => format!("{}o/a", base),
+        None => participle.to_string(),
+    }
+}
+
+/// Every spelling a form accepts: `sono andato/a` -> `sono andato`, `sono andata`
+pub(crate) fn accepted_forms(form: &str) -> Vec<String> {
+    let mut accepted = vec![String::new()];
+
+    for word in form.split_whitespace() {
+        let alternatives: Vec<String> = match word.split_once('/') {
+            Some((first, ending)) if !ending.is_empty() && first.len() > ending.len() => {
+                let base = &first[..first.len() - ending.len()];
+                vec![first.to_string(), format!("{}{}", base, ending)]
+            }
+            _ => vec![word.to_string()],
+        };
+
+        accepted = accepted.iter()
+            .flat_map(|prefix| alternatives.iter().map(move |alternative| match prefix.is_empty() {
+                true => alternative.clone(),
+                false => format!("{} {}", prefix, alternative),
+            }))
+            .collect();
+    }
+
+    accepted
+}
+
+/// Generates a conjugation table, using overrides wherever they are given
+pub(crate) fn conjugate(
+    infinitive: &str,
+    auxiliary: Option<Auxiliary>,
+    overrides: &ConjugationOverrides,
+) -> PreferenceResult<ConjugationTable> {
+    let infinitive = normalize_headword(infinitive);
+
+    // Reflexive verbs (lavarsi) are conjugated as their base verb with pronouns
+    let (base, reflexive) = match infinitive.strip_suffix("si") {
+        Some(base) if base.ends_with("ar") || base.ends_with("er") || base.ends_with("ir") => {
+            (format!("{}e", base), true)
+        }
+        _ => (infinitive.clone(), false),
+    };
+
+    let class = if base.ends_with("are") {
+        Class::Are
+    } else if base.ends_with("ere") {
+        Class::Ere
+    } else if base.ends_with("ire") {
+        Class::Ire
+    } else {
+        return Err(AppError::invalid("infinitive", format!("'{}' is not a regular -are, -ere or -ire infinitive", infinitive)));
+    };
+
+    let stem = &base[..base.len() - 3];
+    let isc = class == Class::Ire
+        && overrides.isc.unwrap_or_else(|| !NON_ISC_VERBS.contains(&base.as_str()));
+    let auxiliary = match reflexive {
+        true => Auxiliary::Essere,
+        false => auxiliary.unwrap_or(Auxiliary::Avere),
+    };
+
+    let participle = overrides.participio.clone()
+        .unwrap_or_else(|| format!("{}{}", stem, match class {
+            Class::Are => "ato",
+            Class::Ere => "uto",
+            Class::Ire => "ito",
+        }));
+
+    let presente = match (class, isc) {
+        (Class::Are, _) => with_endings(stem, ["o", "i", "a", "iamo", "ate", "ano"], class),
+        (Class::Ere, _) => with_endings(stem, ["o", "i", "e", "iamo", "ete", "ono"], class),
+        (Class::Ire, false) => with_endings(stem, ["o", "i", "e", "iamo", "ite", "ono"], class),
+        (Class::Ire, true) => with_endings(stem, ["isco", "isci", "isce", "iamo", "ite", "iscono"], class),
+    };
+
+    let imperfetto_stem = format!("{}{}", stem, class.vowel());
+    let imperfetto = with_endings(&imperfetto_stem, ["vo", "vi", "va", "vamo", "vate", "vano"], class);
+
+    // -ciare/-giare drop the i (mangerò), -care/-gare add an h (cercherò)
+    let futuro_stem = match class {
+        Class::Are if stem.ends_with("ci") || stem.ends_with("gi") => format!("{}er", &stem[..stem.len() - 1]),
+        Class::Are => join(stem, "er", class),
+        Class::Ere => format!("{}er", stem),
+        Class::Ire => format!("{}ir", stem),
+    };
+    let futuro = with_endings(&futuro_stem, ["ò", "ai", "à", "emo", "ete", "anno"], class);
+    let condizionale = with_endings(&futuro_stem, ["ei", "esti", "ebbe", "emmo", "este", "ebbero"], class);
+
+    let congiuntivo = match (class, isc) {
+        (Class::Are, _) => with_endings(stem, ["i", "i", "i", "iamo", "iate", "ino"], class),
+        (_, false) => with_endings(stem, ["a", "a", "a", "iamo", "iate", "ano"], class),
+        (_, true) => with_endings(stem, ["isca", "isca", "isca", "iamo", "iate", "iscano"], class),
+    };
+
+    let passato_prossimo = (0..6)
+        .map(|person| match auxiliary {
+            Auxiliary::Avere => format!("{} {}", AVERE_PRESENT[person], participle),
+            Auxiliary::Essere => format!("{} {}", ESSERE_PRESENT[person], agreeing_participle(&participle, person >= 3)),
+        })
+        .collect();
+
+    let mut tenses = BTreeMap::from([
+        (Tense::Presente, presente),
+        (Tense::PassatoProssimo, passato_prossimo),
+        (Tense::Imperfetto, imperfetto),
+        (Tense::Futuro, futuro),
+        (Tense::Condizionale, condizionale),
+        (Tense::Congiuntivo, congiuntivo),
+    ]);
+
+    if reflexive {
+        for forms in tenses.values_mut() {
+            for (person, form) in forms.iter_mut().enumerate() {
+                *form = format!("{} {}", REFLEXIVE_PRONOUNS[person], form);
+            }
+        }
+    }
+
+    // Irregular forms replace generated ones person by person
+    for (tense, forms) in &overrides.tenses {
+        if forms.len() != PERSONS.len() {
+            return Err(AppError::invalid("conjugation", format!("Conjugation override for {} must list 6 forms", tense.key())));
+        }
+
+        let generated = tenses.entry(*tense).or_default();
+        for (person, form) in forms.iter().enumerate() {
+            if let Some(form) = form.as_deref().map(str::trim).filter(|form| !form.is_empty()) {
+                generated[person] = form.to_string();
+            }
+        }
+    }
+
+    Ok(ConjugationTable {
+        infinitive,
+        auxiliary,
+        participle,
+        persons: PERSONS,
+        tenses,
+        warnings: overrides.ignored.clone(),
+    })
+}
+
+/// Conjugation table of a verb card
+pub(crate) fn card_conjugation(card: &CardRecord) -> PreferenceResult<ConjugationTable> {
+    if !is_verb(card) {
+        return Err(AppError::invalid("cardId", format!("Card '{}' is not a verb", card.id)));
+    }
+
+    let auxiliary = match card.auxiliary.as_deref().map(|auxiliary| fold(auxiliary.trim())) {
+        None => None,
+        Some(auxiliary) if auxiliary == "essere" => Some(Auxiliary::Essere),
+        Some(auxiliary) if auxiliary == "avere" => Some(Auxiliary::Avere),
+        Some(auxiliary) => {
+            return Err(AppError::invalid("auxiliary", format!("Unknown auxiliary '{}', expected essere or avere", auxiliary)));
+        }
+    };
+
+    conjugate(&card.italian, auxiliary, &card.conjugation.clone().unwrap_or_default())
+}
+
+/// Generates the conjugation table of a verb card
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_conjugation(
+    app: AppHandle,
+    directory_path: String,
+    card_id: String,
+) -> PreferenceResult<ConjugationTable> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+
+    let card = snapshot.cards.iter()
+        .find(|card| card.id == card_id)
+        .ok_or_else(|| AppError::not_found(format!("Card '{}' does not exist", card_id)))?;
+
+    card_conjugation(card)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forms(infinitive: &str, tense: Tense) -> Vec<String> {
+        conjugate(infinitive, None, &ConjugationOverrides::default()).unwrap().tenses[&tense].clone()
+    }
+
+    #[test]
+    fn regular_verbs_of_each_class() {
+        for (infinitive, presente, futuro, congiuntivo) in [
+            (
+                "parlare",
+                ["parlo", "parli", "parla", "parliamo", "parlate", "parlano"],
+                ["parlerò", "parlerai", "parlerà", "parleremo", "parlerete", "parleranno"],
+                ["parli", "parli", "parli", "parliamo", "parliate", "parlino"],
+            ),
+            (
+                "credere",
+                ["credo", "credi", "crede", "crediamo", "credete", "credono"],
+                ["crederò", "crederai", "crederà", "crederemo", "crederete", "crederanno"],
+                ["creda", "creda", "creda", "crediamo", "crediate", "credano"],
+            ),
+            (
+                "dormire",
+                ["dormo", "dormi", "dorme", "dormiamo", "dormite", "dormono"],
+                ["dormirò", "dormirai", "dormirà", "dormiremo", "dormirete", "dormiranno"],
+                ["dorma", "dorma", "dorma", "dormiamo", "dormiate", "dormano"],
+            ),
+        ] {
+            assert_eq!(forms(infinitive, Tense::Presente), presente, "{}", infinitive);
+            assert_eq!(forms(infinitive, Tense::Futuro), futuro, "{}", infinitive);
+            assert_eq!(forms(infinitive, Tense::Congiuntivo), congiuntivo, "{}", infinitive);
+        }
+
+        assert_eq!(forms("credere", Tense::Imperfetto)[0], "credevo");
+        assert_eq!(forms("parlare", Tense::Condizionale)[2], "parlerebbe");
+        assert_eq!(forms("credere", Tense::PassatoProssimo)[0], "ho creduto");
+        assert_eq!(forms("dormire", Tense::PassatoProssimo)[3], "abbiamo dormito");
+    }
+
+    #[test]
+    fn isc_verbs_and_the_isc_override() {
+        assert_eq!(forms("finire", Tense::Presente), ["finisco", "finisci", "finisce", "finiamo", "finite", "finiscono"]);
+        assert_eq!(forms("finire", Tense::Congiuntivo), ["finisca", "finisca", "finisca", "finiamo", "finiate", "finiscano"]);
+
+        let overrides = ConjugationOverrides { isc: Some(false), ..Default::default() };
+        let table = conjugate("finire", None, &overrides).unwrap();
+        assert_eq!(table.tenses[&Tense::Presente][0], "fino");
+    }
+
+    #[test]
+    fn c_and_g_stems_keep_their_hard_sound() {
+        assert_eq!(forms("cercare", Tense::Presente), ["cerco", "cerchi", "cerca", "cerchiamo", "cercate", "cercano"]);
+        assert_eq!(forms("cercare", Tense::Futuro)[0], "cercherò");
+        assert_eq!(forms("pagare", Tense::Congiuntivo)[5], "paghino");
+        assert_eq!(forms("pagare", Tense::Condizionale)[0], "pagherei");
+        assert_eq!(forms("cercare", Tense::Imperfetto)[0], "cercavo");
+    }
+
+    #[test]
+    fn ciare_and_giare_drop_the_i() {
+        assert_eq!(forms("mangiare", Tense::Presente), ["mangio", "mangi", "mangia", "mangiamo", "mangiate", "mangiano"]);
+        assert_eq!(forms("mangiare", Tense::Futuro)[0], "mangerò");
+        assert_eq!(forms("cominciare", Tense::Condizionale)[0], "comincerei");
+        assert_eq!(forms("mangiare", Tense::Congiuntivo), ["mangi", "mangi", "mangi", "mangiamo", "mangiate", "mangino"]);
+    }
+
+    #[test]
+    fn essere_participles_agree_in_gender_and_number() {
+        let table = conjugate("arrivare", Some(Auxiliary::Essere), &ConjugationOverrides::default()).unwrap();
+        let passato = &table.tenses[&Tense::PassatoProssimo];
+
+        assert_eq!(passato[0], "sono arrivato/a");
+        assert_eq!(passato[3], "siamo arrivati/e");
+        assert_eq!(accepted_forms(&passato[3]), ["siamo arrivati", "siamo arrivate"]);
+    }
+
+    #[test]
+    fn reflexive_verbs_take_pronouns_and_essere() {
+        let table = conjugate("lavarsi", Some(Auxiliary::Avere), &ConjugationOverrides::default()).unwrap();
+
+        assert_eq!(table.auxiliary, Auxiliary::Essere);
+        assert_eq!(table.tenses[&Tense::Presente], ["mi lavo", "ti lavi", "si lava", "ci laviamo", "vi lavate", "si lavano"]);
+        assert_eq!(table.tenses[&Tense::PassatoProssimo][0], "mi sono lavato/a");
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_generated_forms() {
+        let overrides: ConjugationOverrides = serde_yaml::from_str(
+            "participio: andato\npresente: [vado, vai, va, ~, ~, vanno]",
+        ).unwrap();
+        let table = conjugate("andare", Some(Auxiliary::Essere), &overrides).unwrap();
+
+        assert_eq!(table.tenses[&Tense::Presente], ["vado", "vai", "va", "andiamo", "andate", "vanno"]);
+        assert_eq!(table.tenses[&Tense::PassatoProssimo][0], "sono andato/a");
+
+        let overrides = ConjugationOverrides { participio: Some("rotto".into()), ..Default::default() };
+        assert_eq!(conjugate("rompere", None, &overrides).unwrap().tenses[&Tense::PassatoProssimo][0], "ho rotto");
+    }
+
+    #[test]
+    fn override_lists_need_six_forms() {
+        let overrides: ConjugationOverrides = serde_yaml::from_str("futuro: [andrò]").unwrap();
+
+        assert!(conjugate("andare", None, &overrides).is_err());
+    }
+
+    #[test]
+    fn unknown_or_malformed_tenses_are_skipped_and_reported() {
+        let overrides: ConjugationOverrides = serde_yaml::from_str(
+            "presnte: [vado]\nimperfetto: 3\nfuturo: [andrò, andrai, andrà, andremo, andrete, andranno]",
+        ).unwrap();
+
+        assert_eq!(overrides.tenses.keys().collect::<Vec<_>>(), [&Tense::Futuro]);
+        assert_eq!(overrides.ignored.len(), 2);
+        assert!(overrides.ignored.iter().any(|reason| reason.contains("'presnte'")));
+
+        let table = conjugate("andare", None, &overrides).unwrap();
+        assert_eq!(table.tenses[&Tense::Futuro][0], "andrò");
+        assert_eq!(table.warnings, overrides.ignored);
+
+        // The deck index caches overrides as JSON
+        let cached: ConjugationOverrides = serde_json::from_value(serde_json::to_value(&overrides).unwrap()).unwrap();
+        assert_eq!(cached.tenses, overrides.tenses);
+        assert_eq!(cached.ignored, overrides.ignored);
+    }
+
+    #[test]
+    fn rejects_words_that_are_not_infinitives() {
+        assert!(conjugate("casa", None, &ConjugationOverrides::default()).is_err());
+    }
+}
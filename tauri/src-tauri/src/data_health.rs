@@ -0,0 +1,223 @@
+// data_health.rs - Quarantine of unreadable stored values instead of silently resetting them
+use std::collections::BTreeMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+use chrono::{DateTime, Utc};
+
+use crate::error::AppError;
+use crate::migrations;
+use crate::scheduling::{self, DeckSchedules, ReviewLog};
+use crate::{
+    emit_to_frontend, get_store, save_preference, save_store, AppMeta, PreferenceResult, ProfileUser, Progress,
+    VocabularyProgress, APP_META_KEY, DECK_PROGRESS_KEY, PROFILE_USER_KEY, PROGRESS_KEY,
+};
+
+/// Store key holding values set aside because they could not be read. Only written by the backend.
+pub(crate) const QUARANTINE_KEY: &str = "quarantinedData";
+
+/// An unreadable value kept until the learner decides what to do with it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedValue {
+    pub id: String,
+    /// Store key the value was read from
+    pub key: String,
+    /// The value exactly as it was stored
+    pub value: Value,
+    pub reason: String,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// A stored value that cannot be read, found by a health check
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyProblem {
+    pub key: String,
+    pub message: String,
+}
+
+/// State of the active profile's stored data
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DataHealth {
+    pub healthy: bool,
+    pub schema_version: u64,
+    /// Values set aside and awaiting a decision; their keys cannot be saved meanwhile
+    pub quarantined: Vec<QuarantinedValue>,
+    /// Values that are unreadable but were not needed yet
+    pub problems: Vec<KeyProblem>,
+}
+
+fn check<T: DeserializeOwned>(value: &Value) -> Result<(), String> {
+    serde_json::from_value::<T>(value.clone())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Checks the shape of the keys the app relies on; other keys are accepted as they are
+pub(crate) fn check_shape(key: &str, value: &Value) -> Result<(), String> {
+    match key {
+        PROFILE_USER_KEY => check::<ProfileUser>(value),
+        PROGRESS_KEY => check::<Progress>(value),
+        APP_META_KEY => check::<AppMeta>(value),
+        DECK_PROGRESS_KEY => check::<BTreeMap<String, VocabularyProgress>>(value),
+        scheduling::CARD_SCHEDULES_KEY => check::<BTreeMap<String, DeckSchedules>>(value),
+        scheduling::REVIEW_HISTORY_KEY => check::<Vec<ReviewLog>>(value),
+        _ => Ok(()),
+    }
+}
+
+fn load_quarantine(app: &AppHandle) -> PreferenceResult<Vec<QuarantinedValue>> {
+    let store = get_store(app)?;
+
+    match store.get(QUARANTINE_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::storage(format!("Failed to deserialize quarantined data: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Stores the quarantine list without touching the keys it refers to
+fn save_quarantine(app: &AppHandle, quarantine: &[QuarantinedValue]) -> PreferenceResult<()> {
+    let store = get_store(app)?;
+
+    if quarantine.is_empty() {
+        store.delete(QUARANTINE_KEY);
+    } else {
+        let quarantine_json = serde_json::to_value(quarantine)
+            .map_err(|e| AppError::storage(format!("Failed to serialize quarantined data: {}", e)))?;
+        store.set(QUARANTINE_KEY, quarantine_json);
+    }
+
+    save_store(app, &store)
+}
+
+/// Keeps quarantined values across a bulk preference replacement; they are
+/// only dropped through `resolve_quarantined_value`. Keys with a quarantined
+/// value keep their current value too, as they cannot be saved until it is resolved.
+pub(crate) fn preserve_quarantine(
+    store: &tauri_plugin_store::Store<tauri::Wry>,
+    preferences: &mut serde_json::Map<String, Value>,
+) {
+    let Some(quarantine) = store.get(QUARANTINE_KEY) else {
+        preferences.remove(QUARANTINE_KEY);
+        return;
+    };
+
+    let entries: Vec<QuarantinedValue> = serde_json::from_value(quarantine.clone()).unwrap_or_default();
+    for entry in entries {
+        match store.get(&entry.key) {
+            Some(current) => preferences.insert(entry.key, current),
+            None => preferences.remove(&entry.key),
+        };
+    }
+
+    preferences.insert(QUARANTINE_KEY.to_string(), quarantine);
+}
+
+/// Reads a stored value. An unreadable value is moved into quarantine, the frontend
+/// is told, and `None` is returned as if the key were missing.
+pub(crate) fn load_checked<T: DeserializeOwned>(app: &AppHandle, key: &str) -> PreferenceResult<Option<T>> {
+    let store = get_store(app)?;
+
+    let Some(value) = store.get(key) else {
+        return Ok(None);
+    };
+
+    let error = match serde_json::from_value(value.clone()) {
+        Ok(parsed) => return Ok(Some(parsed)),
+        Err(error) => error,
+    };
+
+    let entry = QuarantinedValue {
+        id: format!("{:016x}", rand::random::<u64>()),
+        key: key.to_string(),
+        value,
+        reason: error.to_string(),
+        quarantined_at: Utc::now(),
+    };
+
+    let mut quarantine = load_quarantine(app)?;
+    quarantine.push(entry.clone());
+
+    // The value moves to the quarantine in the same save, so it is never lost
+    store.delete(key);
+    save_quarantine(app, &quarantine)?;
+
+    let payload = serde_json::to_value(&entry)
+        .map_err(|e| AppError::internal(format!("Failed to serialize quarantined value: {}", e)))?;
+    emit_to_frontend(app, "data-quarantined", payload)?;
+
+    Ok(None)
+}
+
+/// Refuses to save a key while one of its values awaits a decision, so a value
+/// rebuilt from defaults never silently replaces the learner's data
+pub(crate) fn ensure_writable(app: &AppHandle, key: &str) -> PreferenceResult<()> {
+    if load_quarantine(app)?.iter().any(|entry| entry.key == key) {
+        return Err(AppError::Quarantined {
+            key: key.to_string(),
+            message: format!("Saved '{}' could not be read and was set aside; review it before saving new data", key),
+        });
+    }
+
+    Ok(())
+}
+
+/// Reports quarantined values and unreadable stored values of the active profile
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_data_health(app: AppHandle) -> PreferenceResult<DataHealth> {
+    let store = get_store(&app)?;
+    let quarantined = load_quarantine(&app)?;
+
+    let problems: Vec<KeyProblem> = store.entries()
+        .into_iter()
+        .filter_map(|(key, value)| {
+            check_shape(&key, &value).err().map(|message| KeyProblem { key, message })
+        })
+        .collect();
+
+    let schema_version = store.get(migrations::SCHEMA_VERSION_KEY)
+        .and_then(|value| value.as_u64())
+        .unwrap_or(0);
+
+    Ok(DataHealth {
+        healthy: quarantined.is_empty() && problems.is_empty(),
+        schema_version,
+        quarantined,
+        problems,
+    })
+}
+
+/// Resolves a quarantined value. With a `replacement` (e.g. the value repaired by the
+/// learner) it is checked and saved under the original key; without one the
+/// quarantined value is discarded, confirming that the key starts over.
+#[tauri::command(rename_all = "camelCase")]
+pub fn resolve_quarantined_value(app: AppHandle, id: String, replacement: Option<Value>) -> PreferenceResult<()> {
+    let mut quarantine = load_quarantine(&app)?;
+
+    let position = quarantine.iter()
+        .position(|entry| entry.id == id)
+        .ok_or_else(|| AppError::not_found(format!("Quarantined value '{}' does not exist", id)))?;
+
+    if let Some(replacement) = &replacement {
+        let key = &quarantine[position].key;
+        check_shape(key, replacement)
+            .map_err(|reason| AppError::invalid("replacement", format!("Replacement is not valid: {}", reason)))?;
+
+        // Saving is refused while any value of the key is pending, so resolve the others first
+        if quarantine.iter().filter(|entry| entry.key == *key).count() > 1 {
+            return Err(AppError::invalid("id", format!("'{}' has other quarantined values; resolve them first", key)));
+        }
+    }
+
+    let entry = quarantine.remove(position);
+    save_quarantine(&app, &quarantine)?;
+
+    match replacement {
+        Some(replacement) => save_preference(app, entry.key, replacement),
+        None => Ok(()),
+    }
+}
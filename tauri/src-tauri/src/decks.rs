@@ -0,0 +1,235 @@
+// decks.rs - Recently opened decks registry
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use chrono::{DateTime, Utc};
+
+use crate::error::AppError;
+use crate::filtered_decks::{self, FilteredDeckInfo};
+use crate::index::{self, DeckSnapshot};
+use crate::scope;
+use crate::{get_store, load_deck_progress, save_preference, PreferenceResult, VocabularyProgress};
+
+/// Store key holding the list of recent decks
+const RECENT_DECKS_KEY: &str = "recentDecks";
+
+/// Unpinned decks beyond this count are dropped, oldest first
+const MAX_RECENT_DECKS: usize = 20;
+
+/// A deck the user has opened before
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentDeck {
+    path: String,
+    display_name: String,
+    pinned: bool,
+    last_opened: DateTime<Utc>,
+    card_count: usize,
+    error_count: usize,
+}
+
+/// A recent deck together with its saved study position
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentDeckInfo {
+    #[serde(flatten)]
+    deck: RecentDeck,
+    progress: Option<VocabularyProgress>,
+}
+
+/// An entry of the deck list: a folder deck or a saved filtered deck
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DeckListing {
+    Folder(RecentDeckInfo),
+    Filtered(FilteredDeckInfo),
+}
+
+/// Default display name of a deck: its folder name
+fn default_display_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Reads the registry from the store
+fn load_recent_decks(app: &AppHandle) -> PreferenceResult<Vec<RecentDeck>> {
+    let store = get_store(app)?;
+
+    match store.get(RECENT_DECKS_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::storage(format!("Failed to deserialize recent decks: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Sorts (pinned first, then most recent), prunes and persists the registry
+fn save_recent_decks(app: &AppHandle, mut decks: Vec<RecentDeck>) -> PreferenceResult<()> {
+    decks.sort_by(|deck_a, deck_b| {
+        deck_b.pinned.cmp(&deck_a.pinned)
+            .then(deck_b.last_opened.cmp(&deck_a.last_opened))
+    });
+
+    let mut unpinned = 0;
+    decks.retain(|deck| {
+        if deck.pinned {
+            return true;
+        }
+        unpinned += 1;
+        unpinned <= MAX_RECENT_DECKS
+    });
+
+    let decks_json = serde_json::to_value(&decks)
+        .map_err(|e| AppError::storage(format!("Failed to serialize recent decks: {}", e)))?;
+
+    save_preference(app.clone(), RECENT_DECKS_KEY.to_string(), decks_json)
+}
+
+/// Applies a change to a single registry entry, failing if the deck is unknown
+fn update_recent_deck<F>(app: &AppHandle, path: &str, update: F) -> PreferenceResult<()>
+where
+    F: FnOnce(&mut RecentDeck),
+{
+    let mut decks = load_recent_decks(app)?;
+
+    let deck = decks.iter_mut()
+        .find(|deck| deck.path == path)
+        .ok_or_else(|| AppError::not_found(format!("Deck '{}' is not in the recent decks list", path)))?;
+
+    update(deck);
+
+    save_recent_decks(app, decks)
+}
+
+/// Name shown for a deck: its custom name if it is a recent deck, else its folder name
+pub(crate) fn deck_display_name(app: &AppHandle, path: &str) -> PreferenceResult<String> {
+    let display_name = load_recent_decks(app)?
+        .into_iter()
+        .find(|deck| deck.path == path)
+        .map(|deck| deck.display_name)
+        .unwrap_or_else(|| default_display_name(path));
+
+    Ok(display_name)
+}
+
+/// Records that a deck was opened, keeping its name and pin
+pub(crate) fn record_deck_opened(app: &AppHandle, snapshot: &DeckSnapshot) -> PreferenceResult<()> {
+    let mut decks = load_recent_decks(app)?;
+
+    match decks.iter_mut().find(|deck| deck.path == snapshot.directory_path) {
+        Some(deck) => {
+            deck.last_opened = Utc::now();
+            deck.card_count = snapshot.cards.len();
+            deck.error_count = snapshot.errors.len();
+        }
+        None => decks.push(RecentDeck {
+            path: snapshot.directory_path.clone(),
+            display_name: default_display_name(&snapshot.directory_path),
+            pinned: false,
+            last_opened: Utc::now(),
+            card_count: snapshot.cards.len(),
+            error_count: snapshot.errors.len(),
+        }),
+    }
+
+    save_recent_decks(app, decks)
+}
+
+/// The deck of the previous session, reopened with its saved study position
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredSession {
+    deck: RecentDeck,
+    snapshot: DeckSnapshot,
+    progress: Option<VocabularyProgress>,
+}
+
+/// Reopens the most recently used deck that still exists on disk, together with its
+/// saved progress, or returns null when there is none. The frontend calls it at startup
+/// once the reopened profile is known, and after every profile switch. It is not run from
+/// `initialize_store`: events emitted during setup reach the webview before it listens,
+/// and a deck that fails to load must not block startup.
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn restore_last_session(app: AppHandle) -> PreferenceResult<Option<RestoredSession>> {
+    let decks = load_recent_decks(&app)?;
+
+    // Decks whose folder access was revoked are not reopened
+    let Some(last_deck) = decks.into_iter()
+        .filter(|deck| Path::new(&deck.path).is_dir())
+        .filter(|deck| scope::check_path(&app, &deck.path).is_ok())
+        .max_by_key(|deck| deck.last_opened)
+    else {
+        return Ok(None);
+    };
+
+    let snapshot = index::load_deck(&app, &last_deck.path, false)?;
+    let progress = load_deck_progress(&app)?.remove(&last_deck.path);
+
+    Ok(Some(RestoredSession {
+        deck: last_deck,
+        snapshot,
+        progress,
+    }))
+}
+
+/// Lists recent decks, pinned first and then by last opened
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_recent_decks(app: AppHandle) -> PreferenceResult<Vec<RecentDeckInfo>> {
+    let mut deck_progress = load_deck_progress(&app)?;
+
+    let decks = load_recent_decks(&app)?
+        .into_iter()
+        .map(|deck| RecentDeckInfo {
+            progress: deck_progress.remove(&deck.path),
+            deck,
+        })
+        .collect();
+
+    Ok(decks)
+}
+
+/// Lists recent folder decks followed by saved filtered decks
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_decks(app: AppHandle) -> PreferenceResult<Vec<DeckListing>> {
+    let folders = list_recent_decks(app.clone())?
+        .into_iter()
+        .map(DeckListing::Folder);
+    let filtered = filtered_decks::list_filtered_deck_infos(&app)?
+        .into_iter()
+        .map(DeckListing::Filtered);
+
+    Ok(folders.chain(filtered).collect())
+}
+
+/// Pins or unpins a deck so it is never pruned from the list
+#[tauri::command(rename_all = "camelCase")]
+pub fn pin_recent_deck(app: AppHandle, path: String, pinned: bool) -> PreferenceResult<()> {
+    update_recent_deck(&app, &path, |deck| deck.pinned = pinned)
+}
+
+/// Changes the name shown for a deck
+#[tauri::command(rename_all = "camelCase")]
+pub fn rename_recent_deck(app: AppHandle, path: String, display_name: String) -> PreferenceResult<()> {
+    let display_name = display_name.trim().to_string();
+    if display_name.is_empty() {
+        return Err(AppError::invalid("displayName", "Display name cannot be empty"));
+    }
+
+    update_recent_deck(&app, &path, |deck| deck.display_name = display_name)
+}
+
+/// Removes a deck from the recent decks list (files on disk are untouched)
+#[tauri::command(rename_all = "camelCase")]
+pub fn forget_recent_deck(app: AppHandle, path: String) -> PreferenceResult<()> {
+    let mut decks = load_recent_decks(&app)?;
+    let previous_len = decks.len();
+
+    decks.retain(|deck| deck.path != path);
+
+    if decks.len() == previous_len {
+        return Err(AppError::not_found(format!("Deck '{}' is not in the recent decks list", path)));
+    }
+
+    save_recent_decks(&app, decks)
+}
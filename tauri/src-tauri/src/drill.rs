@@ -0,0 +1,172 @@
+// drill.rs - Conjugation drills scheduled per verb and tense
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Serialize;
+use tauri::AppHandle;
+use chrono::Utc;
+
+use crate::conjugation::{self, accepted_forms, Tense, PERSONS};
+use crate::error::AppError;
+use crate::index;
+use crate::scheduling::{self, CardSchedule, Maturity};
+use crate::text::fold;
+use crate::PreferenceResult;
+
+/// Questions returned when no count is given
+const DEFAULT_DRILL_SIZE: usize = 20;
+
+/// Subject pronouns learners may type before the verb
+const SUBJECT_PRONOUNS: &[&str] = &["io", "tu", "lui", "lei", "noi", "voi", "loro"];
+
+/// A request for one person of one tense of a verb
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DrillQuestion {
+    /// Derived id the verb+tense is scheduled under, e.g. `verbi/andare.md#conj:futuro`
+    pub drill_id: String,
+    pub card_id: String,
+    pub infinitive: String,
+    pub english: Vec<String>,
+    pub tense: Tense,
+    pub person: usize,
+    /// Prompt such as "andare, noi, futuro"
+    pub prompt: String,
+    pub maturity: Maturity,
+}
+
+/// Outcome of a typed answer
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrillResult {
+    pub correct: bool,
+    /// Correct apart from missing or wrong accents; counted as a hard answer
+    pub accent_mistake: bool,
+    pub expected: String,
+    pub schedule: CardSchedule,
+}
+
+/// Derived card id scheduling one tense of a verb
+fn drill_card_id(card_id: &str, tense: Tense) -> String {
+    format!("{}#conj:{}", card_id, tense.key())
+}
+
+/// Lowercases, collapses whitespace and drops a leading subject pronoun
+fn normalize_answer(answer: &str) -> String {
+    let lowered = answer.to_lowercase();
+    let mut words: Vec<&str> = lowered.split_whitespace().collect();
+
+    if words.len() > 1 && SUBJECT_PRONOUNS.contains(&words[0]) {
+        words.remove(0);
+    }
+
+    words.join(" ")
+}
+
+/// Picks drill questions: due verb+tense pairs first (most overdue first),
+/// then ones never drilled, each asking for a random person
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_conjugation_drill(
+    app: AppHandle,
+    directory_path: String,
+    tenses: Option<Vec<Tense>>,
+    count: Option<usize>,
+) -> PreferenceResult<Vec<DrillQuestion>> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+    let schedules = scheduling::load_deck_schedules(&app, &snapshot.directory_path)?;
+    let tenses = tenses.filter(|tenses| !tenses.is_empty()).unwrap_or_else(|| Tense::ALL.to_vec());
+    let now = Utc::now();
+    let mut rng = rand::thread_rng();
+
+    let mut due = Vec::new();
+    let mut new = Vec::new();
+
+    // Verbs that cannot be conjugated (irregular without overrides) are skipped
+    for card in snapshot.cards.iter().filter(|card| conjugation::is_verb(card)) {
+        let Ok(table) = conjugation::card_conjugation(card) else { continue };
+
+        for &tense in &tenses {
+            let drill_id = drill_card_id(&card.id, tense);
+            let schedule = schedules.get(&drill_id);
+            let person = rng.gen_range(0..PERSONS.len());
+
+            let question = DrillQuestion {
+                prompt: format!("{}, {}, {}", table.infinitive, PERSONS[person], tense.label()),
+                card_id: card.id.clone(),
+                infinitive: table.infinitive.clone(),
+                english: card.english.clone(),
+                maturity: Maturity::of(schedule),
+                drill_id,
+                tense,
+                person,
+            };
+
+            match schedule {
+                None => new.push(question),
+                Some(schedule) if schedule.due <= now => due.push((schedule.due, question)),
+                Some(_) => {}
+            }
+        }
+    }
+
+    due.sort_by_key(|(due, _)| *due);
+    new.shuffle(&mut rng);
+
+    Ok(due.into_iter()
+        .map(|(_, question)| question)
+        .chain(new)
+        .take(count.unwrap_or(DEFAULT_DRILL_SIZE))
+        .collect())
+}
+
+/// Checks a typed form and records the answer for the verb+tense.
+/// Answers differing only in accents are accepted as hard answers.
+#[tauri::command(rename_all = "camelCase")]
+pub fn answer_conjugation_drill(
+    app: AppHandle,
+    directory_path: String,
+    card_id: String,
+    tense: Tense,
+    person: usize,
+    answer: String,
+) -> PreferenceResult<DrillResult> {
+    if person >= PERSONS.len() {
+        return Err(AppError::invalid("person", format!("Person must be between 0 and {}", PERSONS.len() - 1)));
+    }
+
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+    let card = snapshot.cards.iter()
+        .find(|card| card.id == card_id)
+        .ok_or_else(|| AppError::not_found(format!("Card '{}' does not exist", card_id)))?;
+
+    let table = conjugation::card_conjugation(card)?;
+    let expected = table.tenses.get(&tense)
+        .and_then(|forms| forms.get(person))
+        .cloned()
+        .ok_or_else(|| AppError::not_found(format!("No {} form for person {}", tense.label(), person)))?;
+
+    let answer = normalize_answer(&answer);
+    let accepted: Vec<String> = accepted_forms(&expected).iter().map(|form| normalize_answer(form)).collect();
+
+    let correct = accepted.contains(&answer);
+    let accent_mistake = !correct && accepted.iter().any(|form| fold(form) == fold(&answer));
+
+    let grade = match (correct, accent_mistake) {
+        (true, _) => 2,
+        (false, true) => 1,
+        (false, false) => 0,
+    };
+
+    let schedule = scheduling::record_review(
+        &app,
+        &snapshot.directory_path,
+        &drill_card_id(&card_id, tense),
+        grade,
+    )?;
+
+    Ok(DrillResult {
+        correct,
+        accent_mistake,
+        expected,
+        schedule,
+    })
+}
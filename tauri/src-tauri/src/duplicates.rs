@@ -0,0 +1,200 @@
+// duplicates.rs - Duplicate and near-duplicate card detection
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use tauri::AppHandle;
+use chrono::Utc;
+
+use crate::authoring::{self, CardDraft};
+use crate::error::AppError;
+use crate::index::{self, card_id, CardRecord};
+use crate::text::{normalize_headword, normalize_translation};
+use crate::PreferenceResult;
+
+/// Hidden folder (skipped by the index) where merged cards are archived
+const ARCHIVE_DIR_NAME: &str = ".archive";
+
+/// A card taking part in a duplicate group
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCard {
+    id: String,
+    path: String,
+    italian: String,
+    english: Vec<String>,
+}
+
+/// Cards whose headwords are equal once case, accents and articles are ignored
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    normalized_headword: String,
+    /// "exact" when the headwords only differ in case, "near" otherwise
+    kind: &'static str,
+    cards: Vec<DuplicateCard>,
+    /// Translations found in more than one card of the group
+    shared_translations: Vec<String>,
+}
+
+/// Translations that appear in at least two of the given cards
+fn shared_translations(cards: &[&CardRecord]) -> Vec<String> {
+    let mut seen_in: BTreeMap<String, (String, usize)> = BTreeMap::new();
+
+    for card in cards {
+        let unique: HashSet<(String, &String)> = card.english.iter()
+            .map(|translation| (normalize_translation(translation), translation))
+            .collect();
+
+        for (normalized, original) in unique {
+            seen_in.entry(normalized)
+                .or_insert_with(|| (original.clone(), 0))
+                .1 += 1;
+        }
+    }
+
+    seen_in.into_values()
+        .filter(|(_, count)| *count > 1)
+        .map(|(original, _)| original)
+        .collect()
+}
+
+/// Groups cards by normalized headword, keeping only groups with more than one card
+pub(crate) fn duplicate_groups(cards: &[CardRecord]) -> Vec<DuplicateGroup> {
+    let mut by_headword: BTreeMap<String, Vec<&CardRecord>> = BTreeMap::new();
+
+    for card in cards {
+        by_headword.entry(normalize_headword(&card.italian))
+            .or_default()
+            .push(card);
+    }
+
+    by_headword.into_iter()
+        .filter(|(headword, group)| group.len() > 1 && !headword.is_empty())
+        .map(|(normalized_headword, group)| {
+            let first_headword = group[0].italian.trim().to_lowercase();
+            let is_exact = group.iter().all(|card| card.italian.trim().to_lowercase() == first_headword);
+
+            DuplicateGroup {
+                normalized_headword,
+                kind: if is_exact { "exact" } else { "near" },
+                shared_translations: shared_translations(&group),
+                cards: group.iter()
+                    .map(|card| DuplicateCard {
+                        id: card.id.clone(),
+                        path: card.path.clone(),
+                        italian: card.italian.clone(),
+                        english: card.english.clone(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Finds cards that share the same headword once case, accents and articles are ignored
+#[tauri::command(rename_all = "camelCase")]
+pub fn find_duplicates(app: AppHandle, directory_path: String) -> PreferenceResult<Vec<DuplicateGroup>> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+
+    Ok(duplicate_groups(&snapshot.cards))
+}
+
+/// Merges translations and tags of duplicate cards into `keep_id`, then deletes
+/// the other files or moves them to the deck's `.archive` folder
+#[tauri::command(rename_all = "camelCase")]
+pub fn merge_duplicates(
+    app: AppHandle,
+    directory_path: String,
+    keep_id: String,
+    merge_ids: Vec<String>,
+    archive: bool,
+) -> PreferenceResult<CardRecord> {
+    if merge_ids.is_empty() {
+        return Err(AppError::invalid("mergeIds", "Select at least one card to merge"));
+    }
+
+    // Every id is resolved and checked before any file is touched, so a bad id
+    // never leaves the kept card rewritten with the others still in place
+    let (root, kept_path) = authoring::existing_card_path(&app, &directory_path, &keep_id)?;
+
+    let mut merge_paths: Vec<PathBuf> = Vec::new();
+    for merge_id in &merge_ids {
+        let (_, path) = authoring::existing_card_path(&app, &directory_path, merge_id)?;
+
+        if path == kept_path {
+            return Err(AppError::invalid("mergeIds", "The kept card cannot also be merged away"));
+        }
+
+        if !merge_paths.contains(&path) {
+            merge_paths.push(path);
+        }
+    }
+
+    let read_card = |path: &Path| -> PreferenceResult<CardRecord> {
+        let card_id = card_id(&root, path);
+        let content = fs::read_to_string(path)
+            .map_err(|e| AppError::io(path, format!("Failed to read card '{}': {}", card_id, e)))?;
+        index::parse_card(&card_id, path, &content)
+    };
+
+    let kept_card = read_card(&kept_path)?;
+    let merged_cards = merge_paths.iter()
+        .map(|path| read_card(path))
+        .collect::<PreferenceResult<Vec<_>>>()?;
+
+    let mut draft = CardDraft::from_record(&kept_card);
+    let mut known_translations: HashSet<String> = draft.english.iter()
+        .map(|translation| normalize_translation(translation))
+        .collect();
+
+    for card in &merged_cards {
+        for translation in &card.english {
+            if known_translations.insert(normalize_translation(translation)) {
+                draft.english.push(translation.clone());
+            }
+        }
+
+        for tag in &card.tags {
+            if !draft.tags.contains(tag) {
+                draft.tags.push(tag.clone());
+            }
+        }
+    }
+
+    // Archive destinations are checked like any other deck path, so a symlink cannot redirect them
+    let archived_paths = merged_cards.iter()
+        .map(|card| {
+            if !archive {
+                return Ok(None);
+            }
+
+            let mut archived_path = authoring::resolve_in_deck(&root, &format!("{}/{}", ARCHIVE_DIR_NAME, card.id))?;
+            if archived_path.exists() {
+                let stamped = format!("{}.{}", Utc::now().format("%Y%m%d%H%M%S"), card.id.replace('/', "_"));
+                archived_path = authoring::resolve_in_deck(&root, &format!("{}/{}", ARCHIVE_DIR_NAME, stamped))?;
+            }
+            Ok(Some(archived_path))
+        })
+        .collect::<PreferenceResult<Vec<_>>>()?;
+
+    let merged = authoring::rewrite_card(&root, &kept_path, draft)?;
+
+    for (card, archived_path) in merged_cards.iter().zip(archived_paths) {
+        let Some(archived_path) = archived_path else {
+            fs::remove_file(&card.path)
+                .map_err(|e| AppError::io(&card.path, format!("Failed to delete card '{}': {}", card.id, e)))?;
+            continue;
+        };
+
+        if let Some(parent) = archived_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AppError::io(parent, format!("Failed to create archive folder '{}': {}", parent.display(), e)))?;
+        }
+
+        fs::rename(&card.path, &archived_path)
+            .map_err(|e| AppError::io(&card.path, format!("Failed to archive card '{}': {}", card.id, e)))?;
+    }
+
+    Ok(merged)
+}
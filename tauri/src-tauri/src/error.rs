@@ -0,0 +1,141 @@
+// error.rs - Typed errors returned by every command
+use std::fmt;
+use std::path::Path;
+use serde::Serialize;
+
+use crate::scope::ScopeError;
+
+/// Error returned to the frontend. Serializes as `{ "code": "...", "message": "...", ...details }`;
+/// the code and details are stable so the UI can show localized messages,
+/// while `message` is an English fallback.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "code", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum AppError {
+    /// A card, deck, preference or other item does not exist
+    NotFound { message: String },
+    /// An argument was rejected; `field` names the offending argument
+    InvalidInput { field: String, message: String },
+    /// A form was rejected; `errors` lists every invalid field at once
+    Validation { errors: Vec<FieldError>, message: String },
+    /// Something with the same name already exists
+    AlreadyExists { path: String, message: String },
+    /// Reading or writing a file or folder failed
+    Io { path: String, message: String },
+    /// A file could not be parsed; `line` is 1-based when known
+    Parse { file: String, line: Option<usize>, message: String },
+    /// The preference store could not be read, written or decoded
+    Storage { message: String },
+    /// A stored value could not be read and was set aside; `key` cannot be saved until it is resolved
+    Quarantined { key: String, message: String },
+    /// The path is outside the folders and files the user granted access to
+    OutOfScope { path: String, message: String },
+    /// A git operation failed
+    Repository { message: String },
+    /// Any other failure
+    Internal { message: String },
+}
+
+/// Why a single form field was rejected
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        AppError::NotFound { message: message.into() }
+    }
+
+    pub fn invalid(field: &str, message: impl Into<String>) -> Self {
+        AppError::InvalidInput { field: field.to_string(), message: message.into() }
+    }
+
+    pub fn validation(errors: Vec<FieldError>) -> Self {
+        let message = errors.iter()
+            .map(|error| format!("{}: {}", error.field, error.message))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        AppError::Validation { errors, message }
+    }
+
+    pub fn already_exists(path: impl AsRef<Path>, message: impl Into<String>) -> Self {
+        AppError::AlreadyExists { path: path.as_ref().to_string_lossy().to_string(), message: message.into() }
+    }
+
+    pub fn io(path: impl AsRef<Path>, message: impl Into<String>) -> Self {
+        AppError::Io { path: path.as_ref().to_string_lossy().to_string(), message: message.into() }
+    }
+
+    pub fn parse(file: impl AsRef<Path>, line: Option<usize>, message: impl Into<String>) -> Self {
+        AppError::Parse { file: file.as_ref().to_string_lossy().to_string(), line, message: message.into() }
+    }
+
+    pub fn storage(message: impl Into<String>) -> Self {
+        AppError::Storage { message: message.into() }
+    }
+
+    pub fn repository(message: impl Into<String>) -> Self {
+        AppError::Repository { message: message.into() }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::Internal { message: message.into() }
+    }
+
+    /// Frontmatter parse error. Frontmatter starts on the first line of the
+    /// note, so the YAML line is also the line in the file.
+    pub fn yaml(file: impl AsRef<Path>, error: &serde_yaml::Error) -> Self {
+        let line = error.location().map(|location| location.line());
+        AppError::parse(file, line, format!("Failed to parse YAML frontmatter: {}", error))
+    }
+
+    /// Attaches the file a parse error came from
+    pub fn in_file(self, file: impl AsRef<Path>) -> Self {
+        match self {
+            AppError::Parse { line, message, .. } => AppError::parse(file, line, message),
+            other => other,
+        }
+    }
+
+    /// Human readable English message
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotFound { message }
+            | AppError::InvalidInput { message, .. }
+            | AppError::Validation { message, .. }
+            | AppError::AlreadyExists { message, .. }
+            | AppError::Io { message, .. }
+            | AppError::Parse { message, .. }
+            | AppError::Storage { message }
+            | AppError::Quarantined { message, .. }
+            | AppError::OutOfScope { message, .. }
+            | AppError::Repository { message }
+            | AppError::Internal { message } => message,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<ScopeError> for AppError {
+    fn from(error: ScopeError) -> Self {
+        let message = error.to_string();
+
+        match error {
+            ScopeError::OutOfScope(path) | ScopeError::Escape(path) => AppError::OutOfScope {
+                path: path.to_string_lossy().to_string(),
+                message,
+            },
+            ScopeError::Invalid { .. } => AppError::invalid("path", message),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+// filtered_decks.rs - Saved queries selecting cards of a deck
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::error::AppError;
+use crate::index::{self, CardRecord, DeckSnapshot};
+use crate::scheduling::{self, CardSchedule, DeckSchedules};
+use crate::scope;
+use crate::text::fold;
+use crate::{get_store, save_preference, PreferenceResult};
+
+/// Store key holding the saved filtered decks
+const FILTERED_DECKS_KEY: &str = "filteredDecks";
+
+/// Cards forgotten at least this many times are leeches
+pub(crate) const LEECH_LAPSES: u32 = 8;
+
+/// Which cards to keep based on their review state
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DueFilter {
+    /// Reviewed before and due now
+    Due,
+    /// Reviewed before and not due yet
+    NotDue,
+    /// Never reviewed
+    New,
+}
+
+/// Criteria of a filtered deck. Lists match any of their entries and the
+/// criteria are combined, so a card must satisfy every non-empty one.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FilteredDeckQuery {
+    pub tags: Vec<String>,
+    /// Folders relative to the deck root, including their subfolders
+    pub folders: Vec<String>,
+    pub parts_of_speech: Vec<String>,
+    pub due: Option<DueFilter>,
+    pub leeches_only: bool,
+    pub added_within_days: Option<u32>,
+}
+
+/// A saved query over one deck
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredDeck {
+    pub id: String,
+    pub name: String,
+    /// Canonical path of the deck the cards are drawn from
+    pub deck_path: String,
+    pub query: FilteredDeckQuery,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A filtered deck with the current size of its selection
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredDeckInfo {
+    #[serde(flatten)]
+    pub deck: FilteredDeck,
+    pub card_count: usize,
+    pub due_count: usize,
+    /// Set when the source deck can no longer be loaded
+    pub error: Option<AppError>,
+}
+
+/// Folds a list for accent and case insensitive comparison, dropping empty entries
+fn folded_list(values: &[String]) -> Vec<String> {
+    values.iter()
+        .map(|value| fold(value.trim()))
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+impl FilteredDeckQuery {
+    /// Trims every criterion and drops empty entries
+    fn normalized(self) -> Self {
+        let clean_list = |values: Vec<String>| -> Vec<String> {
+            values.into_iter()
+                .map(|value| value.trim().trim_matches('/').to_string())
+                .filter(|value| !value.is_empty())
+                .collect()
+        };
+
+        Self {
+            tags: clean_list(self.tags),
+            folders: clean_list(self.folders),
+            parts_of_speech: clean_list(self.parts_of_speech),
+            ..self
+        }
+    }
+
+    /// Whether a card belongs to the selection
+    pub(crate) fn matches(&self, card: &CardRecord, schedule: Option<&CardSchedule>, now: DateTime<Utc>) -> bool {
+        let tags = folded_list(&self.tags);
+        if !tags.is_empty() && !folded_list(&card.tags).iter().any(|tag| tags.contains(tag)) {
+            return false;
+        }
+
+        if !self.folders.is_empty()
+            && !self.folders.iter().any(|folder| card.id.starts_with(&format!("{}/", folder)))
+        {
+            return false;
+        }
+
+        let parts_of_speech = folded_list(&self.parts_of_speech);
+        if !parts_of_speech.is_empty() {
+            let card_part_of_speech = card.part_of_speech.as_deref().map(fold);
+            if !card_part_of_speech.is_some_and(|part| parts_of_speech.contains(&part)) {
+                return false;
+            }
+        }
+
+        let due_matches = match (self.due, schedule) {
+            (None, _) => true,
+            (Some(DueFilter::New), schedule) => schedule.is_none(),
+            (Some(DueFilter::Due), Some(schedule)) => schedule.due <= now,
+            (Some(DueFilter::NotDue), Some(schedule)) => schedule.due > now,
+            (Some(_), None) => false,
+        };
+        if !due_matches {
+            return false;
+        }
+
+        if self.leeches_only && !schedule.is_some_and(|schedule| schedule.lapses >= LEECH_LAPSES) {
+            return false;
+        }
+
+        match (self.added_within_days, card.added) {
+            (Some(days), Some(added)) => added >= now - Duration::days(i64::from(days)),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    /// Cards of a deck matching the query
+    pub(crate) fn select(&self, snapshot: &DeckSnapshot, schedules: &DeckSchedules) -> Vec<CardRecord> {
+        let now = Utc::now();
+
+        snapshot.cards.iter()
+            .filter(|card| self.matches(card, schedules.get(&card.id), now))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Reads the saved filtered decks from the store
+fn load_filtered_decks(app: &AppHandle) -> PreferenceResult<Vec<FilteredDeck>> {
+    let store = get_store(app)?;
+
+    match store.get(FILTERED_DECKS_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::storage(format!("Failed to deserialize filtered decks: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Persists the filtered decks, sorted by name
+fn save_filtered_decks(app: &AppHandle, mut decks: Vec<FilteredDeck>) -> PreferenceResult<()> {
+    decks.sort_by_key(|deck| fold(&deck.name));
+
+    let decks_json = serde_json::to_value(&decks)
+        .map_err(|e| AppError::storage(format!("Failed to serialize filtered decks: {}", e)))?;
+
+    save_preference(app.clone(), FILTERED_DECKS_KEY.to_string(), decks_json)
+}
+
+/// Finds a saved filtered deck by id
+pub(crate) fn find_filtered_deck(app: &AppHandle, id: &str) -> PreferenceResult<FilteredDeck> {
+    load_filtered_decks(app)?
+        .into_iter()
+        .find(|deck| deck.id == id)
+        .ok_or_else(|| AppError::not_found(format!("Filtered deck '{}' does not exist", id)))
+}
+
+/// Loads the source deck of a filtered deck and selects its cards
+pub(crate) fn filtered_deck_cards(
+    app: &AppHandle,
+    deck: &FilteredDeck,
+) -> PreferenceResult<(Vec<CardRecord>, DeckSchedules)> {
+    let snapshot = index::load_deck(app, &deck.deck_path, false)?;
+    let schedules = scheduling::load_deck_schedules(app, &snapshot.directory_path)?;
+
+    Ok((deck.query.select(&snapshot, &schedules), schedules))
+}
+
+/// Describes a filtered deck, reporting rather than failing when its source deck is gone
+pub(crate) fn filtered_deck_info(app: &AppHandle, deck: FilteredDeck) -> FilteredDeckInfo {
+    match filtered_deck_cards(app, &deck) {
+        Ok((cards, schedules)) => {
+            let now = Utc::now();
+            let due_count = cards.iter()
+                .filter(|card| schedules.get(&card.id).is_some_and(|schedule| schedule.due <= now))
+                .count();
+
+            FilteredDeckInfo { card_count: cards.len(), due_count, error: None, deck }
+        }
+        Err(error) => FilteredDeckInfo { card_count: 0, due_count: 0, error: Some(error), deck },
+    }
+}
+
+/// Lists every filtered deck with the current size of its selection
+pub(crate) fn list_filtered_deck_infos(app: &AppHandle) -> PreferenceResult<Vec<FilteredDeckInfo>> {
+    Ok(load_filtered_decks(app)?
+        .into_iter()
+        .map(|deck| filtered_deck_info(app, deck))
+        .collect())
+}
+
+/// Creates a filtered deck, or replaces the one with the given id
+#[tauri::command(rename_all = "camelCase")]
+pub fn save_filtered_deck(
+    app: AppHandle,
+    id: Option<String>,
+    name: String,
+    directory_path: String,
+    query: FilteredDeckQuery,
+) -> PreferenceResult<FilteredDeckInfo> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::invalid("name", "Filtered deck name cannot be empty"));
+    }
+
+    let deck_path = scope::scoped_deck_path(&app, &directory_path)?;
+    let mut decks = load_filtered_decks(&app)?;
+
+    let deck = match id {
+        Some(id) => {
+            let existing = decks.iter_mut()
+                .find(|deck| deck.id == id)
+                .ok_or_else(|| AppError::not_found(format!("Filtered deck '{}' does not exist", id)))?;
+
+            existing.name = name;
+            existing.deck_path = deck_path.to_string_lossy().to_string();
+            existing.query = query.normalized();
+            existing.clone()
+        }
+        None => {
+            let deck = FilteredDeck {
+                id: format!("{:016x}", rand::random::<u64>()),
+                name,
+                deck_path: deck_path.to_string_lossy().to_string(),
+                query: query.normalized(),
+                created_at: Utc::now(),
+            };
+            decks.push(deck.clone());
+            deck
+        }
+    };
+
+    save_filtered_decks(&app, decks)?;
+
+    Ok(filtered_deck_info(&app, deck))
+}
+
+/// Deletes a filtered deck; the cards and their schedules are untouched
+#[tauri::command(rename_all = "camelCase")]
+pub fn delete_filtered_deck(app: AppHandle, id: String) -> PreferenceResult<()> {
+    let mut decks = load_filtered_decks(&app)?;
+    let previous_len = decks.len();
+
+    decks.retain(|deck| deck.id != id);
+
+    if decks.len() == previous_len {
+        return Err(AppError::not_found(format!("Filtered deck '{}' does not exist", id)));
+    }
+
+    save_filtered_decks(&app, decks)
+}
+
+/// Lists saved filtered decks
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_filtered_decks(app: AppHandle) -> PreferenceResult<Vec<FilteredDeckInfo>> {
+    list_filtered_deck_infos(&app)
+}
+
+/// Shows the cards a query would select before it is saved
+#[tauri::command(rename_all = "camelCase")]
+pub fn preview_filtered_deck(
+    app: AppHandle,
+    directory_path: String,
+    query: FilteredDeckQuery,
+) -> PreferenceResult<Vec<CardRecord>> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+    let schedules = scheduling::load_deck_schedules(&app, &snapshot.directory_path)?;
+
+    Ok(query.normalized().select(&snapshot, &schedules))
+}
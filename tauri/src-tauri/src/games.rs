@@ -0,0 +1,558 @@
+// games.rs - Match-the-pairs and timed sprint game modes
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use chrono::{DateTime, Utc};
+
+use crate::error::AppError;
+use crate::index::{self, CardRecord};
+use crate::quiz::{self, QuizDirection, QuizQuestion};
+use crate::scope;
+use crate::text::{normalize_headword, normalize_translation};
+use crate::{get_store, save_preference, PreferenceResult};
+
+/// Store key holding high scores, grouped by deck path and then by game mode
+const HIGH_SCORES_KEY: &str = "highScores";
+
+/// High scores kept per deck and mode
+const MAX_HIGH_SCORES: usize = 10;
+
+/// Pairs in a match round when no count is given
+const DEFAULT_MATCH_PAIRS: usize = 6;
+
+/// Length of a sprint
+const SPRINT_DURATION: Duration = Duration::from_secs(60);
+
+/// Rounds left unfinished for longer than this are discarded
+const ABANDONED_ROUND_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Match scoring: points per pair, penalty per wrong pairing, and points per
+/// second left of a budget of ten seconds per pair
+const MATCH_PAIR_POINTS: u32 = 100;
+const MATCH_MISTAKE_PENALTY: u32 = 25;
+const MATCH_SECONDS_PER_PAIR: u64 = 10;
+const MATCH_TIME_BONUS: u32 = 5;
+
+/// Sprint scoring: points per correct answer plus a bonus growing with the
+/// streak of consecutive correct answers
+const SPRINT_ANSWER_POINTS: u32 = 10;
+const SPRINT_STREAK_BONUS: u32 = 2;
+const SPRINT_MAX_STREAK_BONUS: u32 = 5;
+
+/// Game modes with their own high score tables
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum GameMode {
+    Match,
+    Sprint,
+}
+
+/// A finished round in a high score table
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HighScore {
+    pub score: u32,
+    pub correct: u32,
+    pub mistakes: u32,
+    pub duration_ms: u64,
+    pub achieved_at: DateTime<Utc>,
+}
+
+/// Outcome of a finished round
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameResult {
+    pub mode: GameMode,
+    pub score: HighScore,
+    /// Position in the deck's high score table, if the round made it
+    pub rank: Option<usize>,
+    pub high_scores: Vec<HighScore>,
+}
+
+/// A word on the board of a match round
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchTile {
+    pub id: String,
+    pub text: String,
+}
+
+/// A new match round: Italian words on the left, English on the right
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchRound {
+    pub round_id: String,
+    pub left: Vec<MatchTile>,
+    pub right: Vec<MatchTile>,
+}
+
+/// Outcome of pairing two tiles
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchAttempt {
+    pub correct: bool,
+    pub remaining: usize,
+    pub mistakes: u32,
+    /// Set once every pair is matched
+    pub result: Option<GameResult>,
+}
+
+/// A sprint question; the answer stays in the backend
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintQuestion {
+    pub direction: QuizDirection,
+    pub prompt: String,
+    pub choices: Vec<String>,
+}
+
+/// A new sprint with its first question
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintRound {
+    pub round_id: String,
+    pub duration_ms: u64,
+    pub question: SprintQuestion,
+}
+
+/// Outcome of a sprint answer
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintAnswer {
+    pub correct: bool,
+    pub correct_index: usize,
+    pub score: u32,
+    pub streak: u32,
+    pub time_left_ms: u64,
+    pub next: Option<SprintQuestion>,
+    /// Set when time ran out; the late answer is not counted
+    pub result: Option<GameResult>,
+}
+
+struct MatchState {
+    deck_path: String,
+    /// Left and right tile ids of each pair
+    pairs: Vec<(String, String)>,
+    matched: HashSet<usize>,
+    mistakes: u32,
+    started: Instant,
+}
+
+struct SprintState {
+    deck_path: String,
+    cards: Vec<CardRecord>,
+    /// Indexes of cards not asked yet in the current pass over the deck
+    queue: Vec<usize>,
+    direction: QuizDirection,
+    question: QuizQuestion,
+    score: u32,
+    correct: u32,
+    mistakes: u32,
+    streak: u32,
+    started: Instant,
+}
+
+enum Round {
+    Match(MatchState),
+    Sprint(SprintState),
+}
+
+impl Round {
+    fn started(&self) -> Instant {
+        match self {
+            Round::Match(state) => state.started,
+            Round::Sprint(state) => state.started,
+        }
+    }
+}
+
+/// Rounds in progress, keyed by round id
+#[derive(Default)]
+pub struct GameState {
+    rounds: Mutex<HashMap<String, Round>>,
+}
+
+impl GameState {
+    /// Drops every round in progress
+    pub(crate) fn clear(&self) -> PreferenceResult<()> {
+        self.rounds.lock()
+            .map_err(|_| AppError::internal("Game state lock was poisoned"))?
+            .clear();
+
+        Ok(())
+    }
+
+    /// Stores a new round, dropping rounds that were abandoned
+    fn insert(&self, round: Round) -> PreferenceResult<String> {
+        let mut rounds = self.rounds.lock()
+            .map_err(|_| AppError::internal("Game state lock was poisoned"))?;
+
+        rounds.retain(|_, round| round.started().elapsed() < ABANDONED_ROUND_AGE);
+
+        let round_id = format!("{:016x}", rand::random::<u64>());
+        rounds.insert(round_id.clone(), round);
+
+        Ok(round_id)
+    }
+}
+
+/// Loads every deck's high scores
+fn load_high_scores(app: &AppHandle) -> PreferenceResult<BTreeMap<String, BTreeMap<GameMode, Vec<HighScore>>>> {
+    let store = get_store(app)?;
+
+    match store.get(HIGH_SCORES_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::storage(format!("Failed to deserialize high scores: {}", e))),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Adds a finished round to the deck's high scores and reports its rank
+fn record_result(app: &AppHandle, deck_path: &str, mode: GameMode, score: HighScore) -> PreferenceResult<GameResult> {
+    let mut all_scores = load_high_scores(app)?;
+    let high_scores = all_scores.entry(deck_path.to_string())
+        .or_default()
+        .entry(mode)
+        .or_default();
+
+    // Equal scores rank below earlier ones
+    let position = high_scores.iter()
+        .position(|high_score| score.score > high_score.score)
+        .unwrap_or(high_scores.len());
+
+    let rank = (position < MAX_HIGH_SCORES).then(|| {
+        high_scores.insert(position, score.clone());
+        high_scores.truncate(MAX_HIGH_SCORES);
+        position + 1
+    });
+    let high_scores = high_scores.clone();
+
+    let scores_json = serde_json::to_value(&all_scores)
+        .map_err(|e| AppError::storage(format!("Failed to serialize high scores: {}", e)))?;
+    save_preference(app.clone(), HIGH_SCORES_KEY.to_string(), scores_json)?;
+
+    Ok(GameResult { mode, score, rank, high_scores })
+}
+
+/// Picks cards whose Italian and English sides are both unambiguous on the board
+fn match_cards(cards: &[CardRecord], pairs: usize) -> Vec<&CardRecord> {
+    let mut shuffled: Vec<&CardRecord> = cards.iter()
+        .filter(|card| !card.english.is_empty())
+        .collect();
+    shuffled.shuffle(&mut rand::thread_rng());
+
+    let mut headwords = HashSet::new();
+    let mut translations = HashSet::new();
+
+    shuffled.into_iter()
+        .filter(|card| {
+            let headword = normalize_headword(&card.italian);
+            let translation = normalize_translation(&card.english[0]);
+
+            match headwords.contains(&headword) || translations.contains(&translation) {
+                true => false,
+                false => {
+                    headwords.insert(headword);
+                    translations.insert(translation);
+                    true
+                }
+            }
+        })
+        .take(pairs)
+        .collect()
+}
+
+/// Asks the next card of a sprint, reshuffling the deck once every card was asked
+fn next_sprint_question(
+    cards: &[CardRecord],
+    queue: &mut Vec<usize>,
+    direction: QuizDirection,
+) -> PreferenceResult<QuizQuestion> {
+    let mut rng = rand::thread_rng();
+
+    // Bounded so a deck without usable distractors cannot loop forever
+    for _ in 0..cards.len() * 2 {
+        if queue.is_empty() {
+            *queue = (0..cards.len()).collect();
+            queue.shuffle(&mut rng);
+        }
+
+        let Some(card_index) = queue.pop() else { break };
+        let direction = quiz::question_direction(direction, &mut rng);
+
+        if let Some(question) = quiz::build_question(&cards[card_index], cards, direction, &mut rng) {
+            return Ok(question);
+        }
+    }
+
+    Err(AppError::invalid("directoryPath", "The deck has no cards with distinct translations to ask"))
+}
+
+fn sprint_question(question: &QuizQuestion) -> SprintQuestion {
+    SprintQuestion {
+        direction: question.direction,
+        prompt: question.prompt.clone(),
+        choices: question.choices.clone(),
+    }
+}
+
+/// Score of a finished sprint
+fn sprint_score(state: &SprintState) -> HighScore {
+    HighScore {
+        score: state.score,
+        correct: state.correct,
+        mistakes: state.mistakes,
+        duration_ms: state.started.elapsed().min(SPRINT_DURATION).as_millis() as u64,
+        achieved_at: Utc::now(),
+    }
+}
+
+/// Starts a match round with up to `pairs` pairs from the deck
+#[tauri::command(rename_all = "camelCase")]
+pub fn start_match_round(
+    app: AppHandle,
+    state: State<'_, GameState>,
+    directory_path: String,
+    pairs: Option<usize>,
+) -> PreferenceResult<MatchRound> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+    let cards = match_cards(&snapshot.cards, pairs.unwrap_or(DEFAULT_MATCH_PAIRS));
+
+    if cards.len() < 2 {
+        return Err(AppError::invalid("directoryPath", "A match round needs at least two cards with distinct words"));
+    }
+
+    let mut left: Vec<MatchTile> = cards.iter()
+        .enumerate()
+        .map(|(position, card)| MatchTile { id: format!("l{}", position), text: card.italian.clone() })
+        .collect();
+    let mut right: Vec<MatchTile> = cards.iter()
+        .enumerate()
+        .map(|(position, card)| MatchTile { id: format!("r{}", position), text: card.english[0].clone() })
+        .collect();
+
+    let pair_ids = left.iter()
+        .zip(&right)
+        .map(|(left_tile, right_tile)| (left_tile.id.clone(), right_tile.id.clone()))
+        .collect();
+
+    let mut rng = rand::thread_rng();
+    left.shuffle(&mut rng);
+    right.shuffle(&mut rng);
+
+    let round_id = state.insert(Round::Match(MatchState {
+        deck_path: snapshot.directory_path,
+        pairs: pair_ids,
+        matched: HashSet::new(),
+        mistakes: 0,
+        started: Instant::now(),
+    }))?;
+
+    Ok(MatchRound { round_id, left, right })
+}
+
+/// Pairs a left tile with a right tile; the round ends when every pair is matched
+#[tauri::command(rename_all = "camelCase")]
+pub fn submit_match(
+    app: AppHandle,
+    state: State<'_, GameState>,
+    round_id: String,
+    left_id: String,
+    right_id: String,
+) -> PreferenceResult<MatchAttempt> {
+    let mut rounds = state.rounds.lock()
+        .map_err(|_| AppError::internal("Game state lock was poisoned"))?;
+
+    let Some(Round::Match(round)) = rounds.get_mut(&round_id) else {
+        return Err(AppError::not_found(format!("Match round '{}' does not exist", round_id)));
+    };
+
+    let pair_index = round.pairs.iter()
+        .position(|(pair_left, _)| *pair_left == left_id)
+        .ok_or_else(|| AppError::not_found(format!("Tile '{}' is not on the board", left_id)))?;
+
+    if round.matched.contains(&pair_index) {
+        return Err(AppError::invalid("leftId", format!("Tile '{}' is already matched", left_id)));
+    }
+
+    let correct = round.pairs[pair_index].1 == right_id;
+    match correct {
+        true => {
+            round.matched.insert(pair_index);
+        }
+        false => round.mistakes += 1,
+    }
+
+    let remaining = round.pairs.len() - round.matched.len();
+    let mistakes = round.mistakes;
+
+    if remaining > 0 {
+        return Ok(MatchAttempt { correct, remaining, mistakes, result: None });
+    }
+
+    let Some(Round::Match(round)) = rounds.remove(&round_id) else {
+        return Err(AppError::not_found(format!("Match round '{}' does not exist", round_id)));
+    };
+    drop(rounds);
+
+    let elapsed = round.started.elapsed();
+    let pairs = round.pairs.len() as u32;
+    let seconds_left = (u64::from(pairs) * MATCH_SECONDS_PER_PAIR).saturating_sub(elapsed.as_secs()) as u32;
+
+    let score = HighScore {
+        score: (pairs * MATCH_PAIR_POINTS + seconds_left * MATCH_TIME_BONUS)
+            .saturating_sub(round.mistakes * MATCH_MISTAKE_PENALTY),
+        correct: pairs,
+        mistakes: round.mistakes,
+        duration_ms: elapsed.as_millis() as u64,
+        achieved_at: Utc::now(),
+    };
+
+    let result = record_result(&app, &round.deck_path, GameMode::Match, score)?;
+
+    Ok(MatchAttempt { correct, remaining, mistakes, result: Some(result) })
+}
+
+/// Starts a 60 second sprint of multiple-choice questions
+#[tauri::command(rename_all = "camelCase")]
+pub fn start_sprint(
+    app: AppHandle,
+    state: State<'_, GameState>,
+    directory_path: String,
+    direction: Option<QuizDirection>,
+) -> PreferenceResult<SprintRound> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+
+    if snapshot.cards.len() < 2 {
+        return Err(AppError::invalid("directoryPath", "A sprint needs at least two cards in the deck"));
+    }
+
+    let direction = direction.unwrap_or(QuizDirection::Mixed);
+    let mut queue = Vec::new();
+    let first_question = next_sprint_question(&snapshot.cards, &mut queue, direction)?;
+    let question = sprint_question(&first_question);
+
+    let round_id = state.insert(Round::Sprint(SprintState {
+        deck_path: snapshot.directory_path,
+        cards: snapshot.cards,
+        queue,
+        direction,
+        question: first_question,
+        score: 0,
+        correct: 0,
+        mistakes: 0,
+        streak: 0,
+        started: Instant::now(),
+    }))?;
+
+    Ok(SprintRound {
+        round_id,
+        duration_ms: SPRINT_DURATION.as_millis() as u64,
+        question,
+    })
+}
+
+/// Answers the current sprint question. Answers arriving after the time is
+/// up end the sprint without being counted.
+#[tauri::command(rename_all = "camelCase")]
+pub fn answer_sprint(
+    app: AppHandle,
+    state: State<'_, GameState>,
+    round_id: String,
+    choice_index: usize,
+) -> PreferenceResult<SprintAnswer> {
+    let mut rounds = state.rounds.lock()
+        .map_err(|_| AppError::internal("Game state lock was poisoned"))?;
+
+    let Some(Round::Sprint(sprint)) = rounds.get_mut(&round_id) else {
+        return Err(AppError::not_found(format!("Sprint '{}' does not exist", round_id)));
+    };
+
+    let correct_index = sprint.question.correct_index;
+    let time_left = SPRINT_DURATION.saturating_sub(sprint.started.elapsed());
+
+    if time_left.is_zero() {
+        let Some(Round::Sprint(sprint)) = rounds.remove(&round_id) else {
+            return Err(AppError::not_found(format!("Sprint '{}' does not exist", round_id)));
+        };
+        drop(rounds);
+
+        let result = record_result(&app, &sprint.deck_path, GameMode::Sprint, sprint_score(&sprint))?;
+
+        return Ok(SprintAnswer {
+            correct: false,
+            correct_index,
+            score: sprint.score,
+            streak: sprint.streak,
+            time_left_ms: 0,
+            next: None,
+            result: Some(result),
+        });
+    }
+
+    let correct = choice_index == correct_index;
+    match correct {
+        true => {
+            sprint.streak += 1;
+            sprint.correct += 1;
+            sprint.score += SPRINT_ANSWER_POINTS
+                + SPRINT_STREAK_BONUS * (sprint.streak - 1).min(SPRINT_MAX_STREAK_BONUS);
+        }
+        false => {
+            sprint.streak = 0;
+            sprint.mistakes += 1;
+        }
+    }
+
+    sprint.question = next_sprint_question(&sprint.cards, &mut sprint.queue, sprint.direction)?;
+
+    Ok(SprintAnswer {
+        correct,
+        correct_index,
+        score: sprint.score,
+        streak: sprint.streak,
+        time_left_ms: time_left.as_millis() as u64,
+        next: Some(sprint_question(&sprint.question)),
+        result: None,
+    })
+}
+
+/// Ends a sprint when its timer runs out (or the learner stops) and records the score
+#[tauri::command(rename_all = "camelCase")]
+pub fn finish_sprint(
+    app: AppHandle,
+    state: State<'_, GameState>,
+    round_id: String,
+) -> PreferenceResult<GameResult> {
+    let sprint = {
+        let mut rounds = state.rounds.lock()
+            .map_err(|_| AppError::internal("Game state lock was poisoned"))?;
+
+        match rounds.remove(&round_id) {
+            Some(Round::Sprint(sprint)) => sprint,
+            Some(round) => {
+                rounds.insert(round_id.clone(), round);
+                return Err(AppError::invalid("roundId", format!("Round '{}' is not a sprint", round_id)));
+            }
+            None => return Err(AppError::not_found(format!("Sprint '{}' does not exist", round_id))),
+        }
+    };
+
+    record_result(&app, &sprint.deck_path, GameMode::Sprint, sprint_score(&sprint))
+}
+
+/// High scores of a deck for every game mode
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_high_scores(
+    app: AppHandle,
+    directory_path: String,
+) -> PreferenceResult<BTreeMap<GameMode, Vec<HighScore>>> {
+    let deck_path = scope::scoped_deck_path(&app, &directory_path)?;
+
+    Ok(load_high_scores(&app)?
+        .remove(deck_path.to_string_lossy().as_ref())
+        .unwrap_or_default())
+}
@@ -0,0 +1,412 @@
+// index.rs - Persistent deck index cache
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::conjugation::ConjugationOverrides;
+use crate::decks;
+use crate::error::AppError;
+use crate::scope;
+use crate::{split_frontmatter, PreferenceResult};
+
+/// Version of the on-disk index format. Bump whenever `CardRecord` or
+/// `IndexEntry` change shape so stale indexes are discarded and rebuilt.
+const INDEX_VERSION: u32 = 5;
+
+/// Directory (inside the app cache dir) holding one index file per deck
+const INDEX_DIR_NAME: &str = "deck-index";
+
+/// A vocabulary note parsed from a markdown file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CardRecord {
+    /// Path relative to the deck root, always using '/' separators
+    pub id: String,
+    pub path: String,
+    pub italian: String,
+    pub italian_forms: Vec<String>,
+    pub english: Vec<String>,
+    pub tags: Vec<String>,
+    pub part_of_speech: Option<String>,
+    pub gender: Option<String>,
+    /// Irregular plural of nouns
+    pub plural: Option<String>,
+    /// Auxiliary of compound tenses for verbs ("essere" or "avere")
+    pub auxiliary: Option<String>,
+    /// Irregular verb forms replacing generated ones
+    pub conjugation: Option<ConjugationOverrides>,
+    pub body: String,
+    /// When the file was created, or first indexed where creation time is unavailable
+    pub added: Option<DateTime<Utc>>,
+}
+
+/// A file that could not be parsed into a card
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CardError {
+    pub id: String,
+    pub path: String,
+    /// 1-based line of the frontmatter error, when known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Cached state of a single markdown file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct IndexEntry {
+    modified_secs: u64,
+    modified_nanos: u32,
+    size: u64,
+    content_hash: String,
+    card: Option<CardRecord>,
+    error: Option<String>,
+    error_line: Option<usize>,
+}
+
+/// On-disk index for one deck directory
+#[derive(Debug, Serialize, Deserialize)]
+struct DeckIndex {
+    version: u32,
+    root: String,
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+/// Counters describing how much work a refresh had to do
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStats {
+    pub files: usize,
+    pub parsed: usize,
+    pub reused: usize,
+    pub removed: usize,
+}
+
+/// Cards of a deck as returned to the frontend
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeckSnapshot {
+    pub directory_path: String,
+    /// Changes whenever any file of the deck is added, removed or edited
+    pub fingerprint: String,
+    pub cards: Vec<CardRecord>,
+    pub errors: Vec<CardError>,
+    pub stats: IndexStats,
+}
+
+/// Frontmatter fields read from a vocabulary note
+#[derive(Debug, Deserialize)]
+struct CardFrontmatter {
+    #[serde(rename = "Italian", deserialize_with = "deserialize_string_list")]
+    italian: Vec<String>,
+    #[serde(rename = "English", deserialize_with = "deserialize_string_list")]
+    english: Vec<String>,
+    #[serde(rename = "Tags", alias = "tags", default, deserialize_with = "deserialize_string_list")]
+    tags: Vec<String>,
+    #[serde(rename = "PartOfSpeech", alias = "partOfSpeech", alias = "pos", default)]
+    part_of_speech: Option<String>,
+    #[serde(rename = "Gender", alias = "gender", default)]
+    gender: Option<String>,
+    #[serde(rename = "Plural", alias = "plural", default)]
+    plural: Option<String>,
+    #[serde(rename = "Auxiliary", alias = "auxiliary", default)]
+    auxiliary: Option<String>,
+    #[serde(rename = "Conjugation", alias = "conjugation", default)]
+    conjugation: Option<ConjugationOverrides>,
+}
+
+/// Accepts either a single string or a list of strings
+fn deserialize_string_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: serde_yaml::Value = Deserialize::deserialize(deserializer)?;
+
+    match value {
+        serde_yaml::Value::Null => Ok(Vec::new()),
+        serde_yaml::Value::String(single) => Ok(vec![single]),
+        serde_yaml::Value::Sequence(items) => items
+            .into_iter()
+            .map(|item| match item {
+                serde_yaml::Value::String(text) => Ok(text),
+                _ => Err(serde::de::Error::custom("Expected a list of strings")),
+            })
+            .collect(),
+        _ => Err(serde::de::Error::custom("Expected a string or a list of strings")),
+    }
+}
+
+/// Parses a markdown note into a card record
+pub(crate) fn parse_card(id: &str, path: &Path, content: &str) -> PreferenceResult<CardRecord> {
+    let (yaml_frontmatter, body) = split_frontmatter(content)
+        .map_err(|error| error.in_file(path))?;
+
+    let frontmatter: CardFrontmatter = serde_yaml::from_str(yaml_frontmatter)
+        .map_err(|error| AppError::yaml(path, &error))?;
+
+    let italian = frontmatter.italian.first()
+        .cloned()
+        .ok_or_else(|| AppError::parse(path, None, "Italian field cannot be empty"))?;
+
+    Ok(CardRecord {
+        id: id.to_string(),
+        path: path.to_string_lossy().to_string(),
+        italian,
+        italian_forms: frontmatter.italian,
+        english: frontmatter.english,
+        tags: frontmatter.tags,
+        part_of_speech: frontmatter.part_of_speech,
+        gender: frontmatter.gender,
+        plural: frontmatter.plural,
+        auxiliary: frontmatter.auxiliary,
+        conjugation: frontmatter.conjugation,
+        body: body.trim().to_string(),
+        added: None,
+    })
+}
+
+/// Hex encoded SHA-256 of the given bytes
+pub(crate) fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Resolves a deck directory to its canonical form, used as the deck key everywhere
+pub(crate) fn canonical_deck_path(directory_path: &str) -> PreferenceResult<PathBuf> {
+    if directory_path.trim().is_empty() {
+        return Err(AppError::invalid("directoryPath", "Directory path cannot be empty"));
+    }
+
+    let path = fs::canonicalize(directory_path)
+        .map_err(|e| AppError::not_found(format!("Directory '{}' does not exist: {}", directory_path, e)))?;
+
+    if !path.is_dir() {
+        return Err(AppError::invalid("directoryPath", format!("Path '{}' is not a directory", directory_path)));
+    }
+
+    Ok(path)
+}
+
+/// Recursively collects markdown files, skipping hidden directories such as `.git`
+fn collect_markdown_files(directory: &Path, files: &mut Vec<PathBuf>) -> PreferenceResult<()> {
+    let entries = fs::read_dir(directory)
+        .map_err(|e| AppError::io(directory, format!("Failed to read directory '{}': {}", directory.display(), e)))?;
+
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let Ok(file_type) = entry.file_type() else { continue };
+        let path = entry.path();
+
+        if file_type.is_dir() {
+            collect_markdown_files(&path, files)?;
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the card id (relative path with '/' separators) for a file inside the deck
+pub(crate) fn card_id(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Loads a previously written index, discarding it if the version or root differ
+fn load_index_file(index_file: &Path, root: &str) -> Option<DeckIndex> {
+    let content = fs::read_to_string(index_file).ok()?;
+    let index: DeckIndex = serde_json::from_str(&content).ok()?;
+
+    (index.version == INDEX_VERSION && index.root == root).then_some(index)
+}
+
+/// Writes the index next to its final location and renames it into place
+fn write_index_file(index_file: &Path, index: &DeckIndex) -> PreferenceResult<()> {
+    if let Some(parent) = index_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io(parent, format!("Failed to create index directory '{}': {}", parent.display(), e)))?;
+    }
+
+    let json_string = serde_json::to_string(index)
+        .map_err(|e| AppError::internal(format!("Failed to serialize deck index: {}", e)))?;
+
+    let temp_file = index_file.with_extension("json.tmp");
+    fs::write(&temp_file, json_string)
+        .map_err(|e| AppError::io(&temp_file, format!("Failed to write deck index '{}': {}", temp_file.display(), e)))?;
+    fs::rename(&temp_file, index_file)
+        .map_err(|e| AppError::io(index_file, format!("Failed to replace deck index '{}': {}", index_file.display(), e)))?;
+
+    Ok(())
+}
+
+/// Brings the index for `root` up to date, parsing only files whose
+/// size, mtime and content hash no longer match the cached entry
+fn refresh_deck_index(root: &Path, index_file: &Path, force: bool) -> PreferenceResult<DeckSnapshot> {
+    let root_string = root.to_string_lossy().to_string();
+    let mut previous_entries = match force {
+        true => BTreeMap::new(),
+        false => load_index_file(index_file, &root_string)
+            .map(|index| index.entries)
+            .unwrap_or_default(),
+    };
+
+    let mut files = Vec::new();
+    collect_markdown_files(root, &mut files)?;
+
+    let mut stats = IndexStats { files: files.len(), ..Default::default() };
+    let mut entries = BTreeMap::new();
+
+    for path in files {
+        let id = card_id(root, &path);
+        let Ok(metadata) = fs::metadata(&path) else { continue };
+
+        let modified = metadata.modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let size = metadata.len();
+
+        let cached = previous_entries.remove(&id);
+
+        // Keep the first time a card was seen across edits of the file
+        let added = cached.as_ref()
+            .and_then(|entry| entry.card.as_ref())
+            .and_then(|card| card.added)
+            .or_else(|| metadata.created().ok().map(DateTime::<Utc>::from))
+            .unwrap_or_else(Utc::now);
+
+        // Unchanged metadata: trust the cached entry without touching the file
+        if let Some(entry) = &cached {
+            if entry.size == size
+                && entry.modified_secs == modified.as_secs()
+                && entry.modified_nanos == modified.subsec_nanos()
+            {
+                stats.reused += 1;
+                entries.insert(id, entry.clone());
+                continue;
+            }
+        }
+
+        let Ok(bytes) = fs::read(&path) else { continue };
+        let hash = content_hash(&bytes);
+
+        let (card, error, error_line) = match cached {
+            // Touched but identical content: keep the parsed card
+            Some(entry) if entry.content_hash == hash => {
+                stats.reused += 1;
+                (entry.card, entry.error, entry.error_line)
+            }
+            _ => {
+                stats.parsed += 1;
+                let parsed = String::from_utf8(bytes)
+                    .map_err(|_| AppError::parse(&path, None, "File is not valid UTF-8"))
+                    .and_then(|content| parse_card(&id, &path, &content))
+                    .map(|card| CardRecord { added: Some(added), ..card });
+
+                match parsed {
+                    Ok(card) => (Some(card), None, None),
+                    Err(AppError::Parse { line, message, .. }) => (None, Some(message), line),
+                    Err(error) => (None, Some(error.to_string()), None),
+                }
+            }
+        };
+
+        entries.insert(id, IndexEntry {
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            size,
+            content_hash: hash,
+            card,
+            error,
+            error_line,
+        });
+    }
+
+    stats.removed = previous_entries.len();
+
+    let index = DeckIndex {
+        version: INDEX_VERSION,
+        root: root_string.clone(),
+        entries,
+    };
+
+    write_index_file(index_file, &index)?;
+
+    let mut fingerprint = Sha256::new();
+    for (id, entry) in &index.entries {
+        fingerprint.update(id.as_bytes());
+        fingerprint.update(entry.content_hash.as_bytes());
+    }
+
+    let mut cards = Vec::new();
+    let mut errors = Vec::new();
+
+    for (id, entry) in index.entries {
+        match (entry.card, entry.error) {
+            (Some(card), _) => cards.push(card),
+            (None, message) => errors.push(CardError {
+                path: root.join(&id).to_string_lossy().to_string(),
+                id,
+                line: entry.error_line,
+                message: message.unwrap_or_else(|| "Unknown parse error".to_string()),
+            }),
+        }
+    }
+
+    Ok(DeckSnapshot {
+        directory_path: root_string,
+        fingerprint: content_hash(&fingerprint.finalize()),
+        cards,
+        errors,
+        stats,
+    })
+}
+
+/// Location of the index file for a deck root
+fn index_file_path(app: &AppHandle, root: &Path) -> PreferenceResult<PathBuf> {
+    let cache_dir = app.path().app_cache_dir()
+        .map_err(|e| AppError::storage(format!("Failed to resolve app cache directory: {}", e)))?;
+
+    let root_hash = content_hash(root.to_string_lossy().as_bytes());
+
+    Ok(cache_dir.join(INDEX_DIR_NAME).join(format!("{}.json", &root_hash[..16])))
+}
+
+/// Loads all cards of a deck through the index cache
+pub(crate) fn load_deck(app: &AppHandle, directory_path: &str, force: bool) -> PreferenceResult<DeckSnapshot> {
+    let root = scope::scoped_deck_path(app, directory_path)?;
+    let index_file = index_file_path(app, &root)?;
+
+    refresh_deck_index(&root, &index_file, force)
+}
+
+/// Opens a deck directory, re-parsing only files that changed since the last open
+#[tauri::command(rename_all = "camelCase")]
+pub fn open_deck(app: AppHandle, directory_path: String) -> PreferenceResult<DeckSnapshot> {
+    let snapshot = load_deck(&app, &directory_path, false)?;
+
+    decks::record_deck_opened(&app, &snapshot)?;
+
+    Ok(snapshot)
+}
+
+/// Discards the cached index of a deck and parses every file again
+#[tauri::command(rename_all = "camelCase")]
+pub fn rebuild_index(app: AppHandle, directory_path: String) -> PreferenceResult<DeckSnapshot> {
+    load_deck(&app, &directory_path, true)
+}
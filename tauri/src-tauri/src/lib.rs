@@ -1,14 +1,41 @@
 // lib.rs - Optimized Rust Backend for Tauri Application
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use serde::de::{self, Deserializer};
-use tauri_plugin_store::StoreExt;
 use tauri::{AppHandle, Emitter};
 use serde_json::{json, Value, Map};
 use chrono::{DateTime, Utc, Duration};
 
+use crate::error::AppError;
+
+mod articles;
+mod authoring;
+mod cloze;
+mod conjugation;
+mod data_health;
+mod decks;
+mod drill;
+mod duplicates;
+mod error;
+mod filtered_decks;
+mod games;
+mod index;
+mod migrations;
+mod preference_merge;
+mod profile_archive;
+mod profiles;
+mod quiz;
+mod repository;
+mod scheduling;
+mod scope;
+mod search;
+mod storage;
+mod study;
+mod text;
+mod validation;
+
 /// Represents user profile data
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -28,6 +55,16 @@ struct Progress {
     daily_goal: u32,
 }
 
+impl Default for Progress {
+    fn default() -> Self {
+        Progress {
+            current_streak: 0,
+            longest_streak: 0,
+            daily_goal: 10,
+        }
+    }
+}
+
 /// Represents application metadata
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -35,6 +72,31 @@ struct AppMeta {
     version: String,
     platform: String,
     last_opened: DateTime<Utc>,
+    /// Version that created this profile's data; `None` for data from before versions were recorded
+    #[serde(default)]
+    first_installed_version: Option<String>,
+    /// Version run before the most recent version change
+    #[serde(default)]
+    previous_version: Option<String>,
+    /// Number of times the app was started with this profile
+    #[serde(default)]
+    launch_count: u64,
+}
+
+impl AppMeta {
+    /// Metadata of the running app, for a profile that has none yet
+    fn new(app: &AppHandle) -> Self {
+        let version = app.package_info().version.to_string();
+
+        AppMeta {
+            first_installed_version: Some(version.clone()),
+            version,
+            platform: get_platform_name(),
+            last_opened: Utc::now(),
+            previous_version: None,
+            launch_count: 0,
+        }
+    }
 }
 
 /// Combined profile data structure
@@ -51,7 +113,8 @@ const PROFILE_USER_KEY: &str = "profileUser";
 const PROGRESS_KEY: &str = "progress";
 const APP_META_KEY: &str = "appMeta";
 
-/// Creates or updates user profile data
+/// Creates or updates user profile data. Fields are normalized to NFC with
+/// collapsed whitespace; invalid fields are reported together.
 #[tauri::command(rename_all = "camelCase")]
 fn create_or_update_profile(
     app: AppHandle,
@@ -59,10 +122,9 @@ fn create_or_update_profile(
     username: String,
     email: String,
 ) -> PreferenceResult<()> {
-    // Validate input
-    if full_name.trim().is_empty() || username.trim().is_empty() || email.trim().is_empty() {
-        return Err("All profile fields are required".into());
-    }
+    // Usernames must be unique across the profiles on this machine
+    let taken_usernames = profiles::other_usernames(&app)?;
+    let fields = validation::validate_profile(&full_name, &username, &email, &taken_usernames)?;
 
     let store = get_store(&app)?;
 
@@ -81,14 +143,14 @@ fn create_or_update_profile(
     };
 
     let profile = ProfileUser {
-        full_name,
-        username,
-        email,
+        full_name: fields.full_name,
+        username: fields.username,
+        email: fields.email,
         created_at,
     };
 
     let profile_json = serde_json::to_value(&profile)
-        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+        .map_err(|e| AppError::storage(format!("Failed to serialize profile: {}", e)))?;
 
     save_preference(app, PROFILE_USER_KEY.to_string(), profile_json)
 }
@@ -100,44 +162,16 @@ fn get_profile_data(app: AppHandle) -> PreferenceResult<ProfileData> {
 
     // Get profile user data
     let profile_user_value = store.get(PROFILE_USER_KEY)
-        .ok_or_else(|| "Profile user data not found".to_string())?;
+        .ok_or_else(|| AppError::not_found("Profile user data not found"))?;
 
     let profile_user: ProfileUser = serde_json::from_value(profile_user_value)
-        .map_err(|e| format!("Failed to deserialize profile user: {}", e))?;
-
-    // Get progress data or create default
-    let progress = match store.get(PROGRESS_KEY) {
-        Some(progress_value) => {
-            serde_json::from_value(progress_value)
-                .unwrap_or_else(|_| Progress {
-                    current_streak: 0,
-                    longest_streak: 0,
-                    daily_goal: 10,
-                })
-        }
-        None => Progress {
-            current_streak: 0,
-            longest_streak: 0,
-            daily_goal: 10,
-        },
-    };
+        .map_err(|e| AppError::storage(format!("Failed to deserialize profile user: {}", e)))?;
+
+    // Get progress data or create default; unreadable progress is quarantined, not discarded
+    let progress = data_health::load_checked(&app, PROGRESS_KEY)?.unwrap_or_default();
 
     // Get app meta data or create default
-    let app_meta = match store.get(APP_META_KEY) {
-        Some(meta_value) => {
-            serde_json::from_value(meta_value)
-                .unwrap_or_else(|_| AppMeta {
-                    version: "1.0.0".to_string(),
-                    platform: get_platform_name(),
-                    last_opened: Utc::now(),
-                })
-        }
-        None => AppMeta {
-            version: "1.0.0".to_string(),
-            platform: get_platform_name(),
-            last_opened: Utc::now(),
-        },
-    };
+    let app_meta = data_health::load_checked(&app, APP_META_KEY)?.unwrap_or_else(|| AppMeta::new(&app));
 
     Ok(ProfileData {
         profile_user,
@@ -154,24 +188,9 @@ fn update_progress(
     longest_streak: Option<u32>,
     daily_goal: Option<u32>,
 ) -> PreferenceResult<()> {
-    let store = get_store(&app)?;
-
-    // Get existing progress or create default
-    let mut progress = match store.get(PROGRESS_KEY) {
-        Some(progress_value) => {
-            serde_json::from_value(progress_value)
-                .unwrap_or_else(|_| Progress {
-                    current_streak: 0,
-                    longest_streak: 0,
-                    daily_goal: 10,
-                })
-        }
-        None => Progress {
-            current_streak: 0,
-            longest_streak: 0,
-            daily_goal: 10,
-        },
-    };
+    // Get existing progress or create default; never build on defaults while progress is quarantined
+    let mut progress: Progress = data_health::load_checked(&app, PROGRESS_KEY)?.unwrap_or_default();
+    data_health::ensure_writable(&app, PROGRESS_KEY)?;
 
     // Update fields if provided
     if let Some(current) = current_streak {
@@ -191,7 +210,7 @@ fn update_progress(
     }
 
     let progress_json = serde_json::to_value(&progress)
-        .map_err(|e| format!("Failed to serialize progress: {}", e))?;
+        .map_err(|e| AppError::storage(format!("Failed to serialize progress: {}", e)))?;
 
     save_preference(app, PROGRESS_KEY.to_string(), progress_json)
 }
@@ -199,14 +218,16 @@ fn update_progress(
 /// Updates app metadata (last opened, etc.)
 #[tauri::command(rename_all = "camelCase")]
 fn update_app_meta(app: AppHandle) -> PreferenceResult<()> {
-    let app_meta = AppMeta {
-        version: "1.0.0".to_string(),
-        platform: get_platform_name(),
-        last_opened: Utc::now(),
-    };
+    // Never rebuild the metadata from defaults while the stored one is quarantined
+    let mut app_meta = data_health::load_checked(&app, APP_META_KEY)?.unwrap_or_else(|| AppMeta::new(&app));
+    data_health::ensure_writable(&app, APP_META_KEY)?;
+
+    app_meta.version = app.package_info().version.to_string();
+    app_meta.platform = get_platform_name();
+    app_meta.last_opened = Utc::now();
 
     let meta_json = serde_json::to_value(&app_meta)
-        .map_err(|e| format!("Failed to serialize app meta: {}", e))?;
+        .map_err(|e| AppError::storage(format!("Failed to serialize app meta: {}", e)))?;
 
     save_preference(app, APP_META_KEY.to_string(), meta_json)
 }
@@ -214,24 +235,13 @@ fn update_app_meta(app: AppHandle) -> PreferenceResult<()> {
 /// Increments current streak and updates last opened
 #[tauri::command(rename_all = "camelCase")]
 fn increment_streak(app: AppHandle) -> PreferenceResult<()> {
-    let store = get_store(&app)?;
+    // Get current progress; never build on defaults while progress is quarantined
+    let mut progress: Progress = data_health::load_checked(&app, PROGRESS_KEY)?.unwrap_or_default();
+    data_health::ensure_writable(&app, PROGRESS_KEY)?;
 
-    // Get current progress
-    let mut progress = match store.get(PROGRESS_KEY) {
-        Some(progress_value) => {
-            serde_json::from_value(progress_value)
-                .unwrap_or_else(|_| Progress {
-                    current_streak: 0,
-                    longest_streak: 0,
-                    daily_goal: 10,
-                })
-        }
-        None => Progress {
-            current_streak: 0,
-            longest_streak: 0,
-            daily_goal: 10,
-        },
-    };
+    // The app meta is updated below; refuse before the streak is saved rather than halfway
+    data_health::load_checked::<AppMeta>(&app, APP_META_KEY)?;
+    data_health::ensure_writable(&app, APP_META_KEY)?;
 
     // Increment streak
     progress.current_streak += 1;
@@ -243,7 +253,7 @@ fn increment_streak(app: AppHandle) -> PreferenceResult<()> {
 
     // Save progress
     let progress_json = serde_json::to_value(&progress)
-        .map_err(|e| format!("Failed to serialize progress: {}", e))?;
+        .map_err(|e| AppError::storage(format!("Failed to serialize progress: {}", e)))?;
 
     save_preference(app.clone(), PROGRESS_KEY.to_string(), progress_json)?;
 
@@ -251,6 +261,50 @@ fn increment_streak(app: AppHandle) -> PreferenceResult<()> {
     update_app_meta(app)
 }
 
+/// Records a launch in the app metadata. When the running version differs from
+/// the recorded one, `app-upgraded` is emitted so migrations and release notes can run.
+fn record_launch(app: &AppHandle) -> PreferenceResult<()> {
+    let version = app.package_info().version.clone();
+
+    let recorded: Option<AppMeta> = data_health::load_checked(app, APP_META_KEY)?;
+    data_health::ensure_writable(app, APP_META_KEY)?;
+    let mut app_meta = recorded.clone().unwrap_or_else(|| AppMeta::new(app));
+
+    // Records from before launches were counted carry a placeholder version
+    let recorded_version = recorded.as_ref()
+        .filter(|recorded| recorded.launch_count > 0)
+        .map(|recorded| recorded.version.clone());
+    let version_changed = recorded.is_some() && recorded_version != Some(version.to_string());
+
+    if version_changed {
+        app_meta.previous_version = recorded_version.clone();
+    }
+
+    app_meta.version = version.to_string();
+    app_meta.platform = get_platform_name();
+    app_meta.last_opened = Utc::now();
+    app_meta.launch_count += 1;
+
+    let meta_json = serde_json::to_value(&app_meta)
+        .map_err(|e| AppError::storage(format!("Failed to serialize app meta: {}", e)))?;
+    save_preference(app.clone(), APP_META_KEY.to_string(), meta_json)?;
+
+    if version_changed {
+        let downgrade = recorded_version.as_deref()
+            .and_then(|previous| semver::Version::parse(previous).ok())
+            .is_some_and(|previous| previous > version);
+
+        let payload = json!({
+            "previousVersion": recorded_version,
+            "version": version.to_string(),
+            "downgrade": downgrade
+        });
+        emit_to_frontend(app, "app-upgraded", payload)?;
+    }
+
+    Ok(())
+}
+
 /// Helper function to get platform name
 fn get_platform_name() -> String {
     #[cfg(target_os = "windows")]
@@ -272,10 +326,10 @@ fn get_days_since_creation(app: AppHandle) -> PreferenceResult<u32> {
     let store = get_store(&app)?;
 
     let profile_user_value = store.get(PROFILE_USER_KEY)
-        .ok_or_else(|| "Profile user data not found".to_string())?;
+        .ok_or_else(|| AppError::not_found("Profile user data not found"))?;
 
     let profile_user: ProfileUser = serde_json::from_value(profile_user_value)
-        .map_err(|e| format!("Failed to deserialize profile user: {}", e))?;
+        .map_err(|e| AppError::storage(format!("Failed to deserialize profile user: {}", e)))?;
 
     let now = Utc::now();
     let duration = now.signed_duration_since(profile_user.created_at);
@@ -302,7 +356,7 @@ pub struct DirectoryEntryInfo {
 }
 
 /// Represents vocabulary learning progress data
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct VocabularyProgress {
     current_index: u32,
     total_cards: u32,
@@ -312,11 +366,12 @@ struct VocabularyProgress {
 }
 
 /// Result type for preference operations
-type PreferenceResult<T> = Result<T, String>;
+type PreferenceResult<T> = Result<T, AppError>;
 
 /// Constants for store configuration
 const STORE_FILE_NAME: &str = "store.json";
 const VOCABULARY_PROGRESS_KEY: &str = "vocabulary_progress";
+const DECK_PROGRESS_KEY: &str = "deckProgress";
 
 impl DirectoryEntryInfo {
     /// Creates a new directory entry information structure
@@ -364,59 +419,70 @@ where
 
 /// Helper function to get store instance with proper error handling
 fn get_store(app: &AppHandle) -> PreferenceResult<Arc<tauri_plugin_store::Store<tauri::Wry>>> {
-    app.store(STORE_FILE_NAME)
-        .map_err(|e| format!("Failed to access store '{}': {}", STORE_FILE_NAME, e))
+    storage::open_store(app)
 }
 
-/// Helper function to save store with proper error handling
-fn save_store(store: &Arc<tauri_plugin_store::Store<tauri::Wry>>) -> PreferenceResult<()> {
-    store.save()
-        .map_err(|e| format!("Failed to save store to disk: {}", e))
+/// Helper function to save the store atomically, keeping rolling backups
+fn save_store(app: &AppHandle, store: &Arc<tauri_plugin_store::Store<tauri::Wry>>) -> PreferenceResult<()> {
+    if let Err(error) = migrations::ensure_migrated(app) {
+        // Drop the refused changes so a later write cannot carry them to disk
+        store.reload()
+            .map_err(|e| AppError::storage(format!("Failed to reload store: {}", e)))?;
+        return Err(error);
+    }
+
+    storage::write_store(app, store)
 }
 
 /// Helper function to emit events to frontend with error handling
 fn emit_to_frontend(app: &AppHandle, event: &str, payload: Value) -> PreferenceResult<()> {
     app.emit_to(tauri::EventTarget::app(), event, payload)
-        .map_err(|e| format!("Failed to emit event '{}' to frontend: {}", event, e))
+        .map_err(|e| AppError::internal(format!("Failed to emit event '{}' to frontend: {}", event, e)))
 }
 
-/// Extracts vocabulary fields from a markdown file with YAML frontmatter
-#[tauri::command(rename_all = "camelCase")]
-fn extract_vocabulary_fields(file_path: String) -> PreferenceResult<VocabularyEntryHeader> {
-    let file_content = fs::read_to_string(&file_path)
-        .map_err(|error| format!("Failed to read file '{}': {}", file_path, error))?;
-
-    // Split content between YAML frontmatter and markdown body
+/// Splits note content into its YAML frontmatter and markdown body
+fn split_frontmatter(file_content: &str) -> PreferenceResult<(&str, &str)> {
     let content_parts: Vec<&str> = file_content.splitn(3, "---").collect();
 
     if content_parts.len() < 3 {
-        return Err("Invalid format: YAML frontmatter delimited by '---' not found".into());
+        return Err(AppError::parse("", None, "Invalid format: YAML frontmatter delimited by '---' not found"));
     }
 
-    let yaml_frontmatter = content_parts[1];
+    Ok((content_parts[1], content_parts[2]))
+}
+
+/// Extracts vocabulary fields from a markdown file with YAML frontmatter
+#[tauri::command(rename_all = "camelCase")]
+fn extract_vocabulary_fields(app: AppHandle, file_path: String) -> PreferenceResult<VocabularyEntryHeader> {
+    let path = scope::check_path(&app, &file_path)?;
+    let file_content = fs::read_to_string(&path)
+        .map_err(|error| AppError::io(&path, format!("Failed to read file '{}': {}", file_path, error)))?;
+
+    let (yaml_frontmatter, _) = split_frontmatter(&file_content)
+        .map_err(|error| error.in_file(&path))?;
 
     let vocabulary_header: VocabularyEntryHeader = serde_yaml::from_str(yaml_frontmatter)
-        .map_err(|error| format!("Failed to parse YAML frontmatter: {}", error))?;
+        .map_err(|error| AppError::yaml(&path, &error))?;
 
     Ok(vocabulary_header)
 }
 
 /// Lists directory contents and sorts them (directories first, then files alphabetically)
 #[tauri::command(rename_all = "camelCase")]
-fn list_directory_contents(directory_path: String) -> PreferenceResult<Vec<DirectoryEntryInfo>> {
+fn list_directory_contents(app: AppHandle, directory_path: String) -> PreferenceResult<Vec<DirectoryEntryInfo>> {
     // Validate directory path
-    let path = PathBuf::from(&directory_path);
+    let path = scope::check_path(&app, &directory_path)?;
     if !path.exists() {
-        return Err(format!("Directory '{}' does not exist", directory_path));
+        return Err(AppError::not_found(format!("Directory '{}' does not exist", directory_path)));
     }
 
     if !path.is_dir() {
-        return Err(format!("Path '{}' is not a directory", directory_path));
+        return Err(AppError::invalid("directoryPath", format!("Path '{}' is not a directory", directory_path)));
     }
 
     // Read the directory contents
-    let directory_entries = fs::read_dir(path)
-        .map_err(|error| format!("Failed to read directory '{}': {}", directory_path, error))?;
+    let directory_entries = fs::read_dir(&path)
+        .map_err(|error| AppError::io(&path, format!("Failed to read directory '{}': {}", directory_path, error)))?;
 
     // Process entries and collect them into a vector
     let mut processed_entries: Vec<DirectoryEntryInfo> = directory_entries
@@ -456,34 +522,107 @@ fn list_directory_contents(directory_path: String) -> PreferenceResult<Vec<Direc
 /// Saves or updates a single preference in the JSON store
 #[tauri::command(rename_all = "camelCase")]
 fn save_preference(app: AppHandle, key: String, value: Value) -> PreferenceResult<()> {
-    // Validate input parameters
+    save_preferences(&app, vec![(key, value)])
+}
+
+/// Keys kept by the backend. Scopes are only granted through the file dialogs;
+/// timestamps, the schema version and quarantined values are written by the backend.
+const BACKEND_KEYS: [&str; 4] = [
+    scope::ALLOWED_SCOPES_KEY,
+    preference_merge::PREFERENCE_TIMESTAMPS_KEY,
+    migrations::SCHEMA_VERSION_KEY,
+    data_health::QUARANTINE_KEY,
+];
+
+/// Refuses empty keys, backend keys and keys whose value awaits a quarantine decision
+fn check_writable_key(app: &AppHandle, key: &str) -> PreferenceResult<()> {
     if key.trim().is_empty() {
-        return Err("Preference key cannot be empty".into());
+        return Err(AppError::invalid("key", "Preference key cannot be empty"));
     }
 
-    let store = get_store(&app)?;
+    if BACKEND_KEYS.contains(&key) {
+        return Err(AppError::invalid("key", format!("Preference '{}' cannot be set directly", key)));
+    }
 
-    // Set the preference value
-    store.set(&key, value.clone());
+    // A quarantined value is only replaced once the learner resolved it
+    data_health::ensure_writable(app, key)
+}
 
-    // Save to disk
-    save_store(&store)?;
+/// Carries the backend-owned values of the store into preferences that replace it
+fn preserve_backend_keys(store: &tauri_plugin_store::Store<tauri::Wry>, preferences: &mut Map<String, Value>) {
+    // Granted scopes and quarantined values survive the replacement and cannot be overwritten
+    scope::preserve_scopes(store.get(scope::ALLOWED_SCOPES_KEY), preferences);
+    data_health::preserve_quarantine(store, preferences);
 
-    // Emit update event to frontend
-    let payload = json!({
-        "key": key,
-        "value": value
-    });
-    emit_to_frontend(&app, "preference-updated", payload)?;
+    // Launch metadata is kept by the backend for this machine and never replaced in bulk
+    match store.get(APP_META_KEY) {
+        Some(meta) => preferences.insert(APP_META_KEY.to_string(), meta),
+        None => preferences.remove(APP_META_KEY),
+    };
+}
+
+/// Saves several preferences with a single write of the store
+fn save_preferences(app: &AppHandle, values: Vec<(String, Value)>) -> PreferenceResult<()> {
+    // Validate input parameters
+    for (key, _) in &values {
+        check_writable_key(app, key)?;
+    }
+
+    let store = get_store(app)?;
+
+    // Set the preference values and record when they changed
+    let mut timestamps = preference_merge::load_timestamps(&store);
+    for (key, value) in &values {
+        store.set(key, value.clone());
+        timestamps.insert(key.clone(), Utc::now());
+    }
+    preference_merge::store_timestamps(&store, &timestamps);
+
+    // Save to disk
+    save_store(app, &store)?;
+
+    // Emit update events to frontend
+    for (key, value) in values {
+        let payload = json!({
+            "key": key,
+            "value": value
+        });
+        emit_to_frontend(app, "preference-updated", payload)?;
+    }
 
     Ok(())
 }
 
 /// Saves or updates all preferences in the JSON store (bulk operation)
 #[tauri::command(rename_all = "camelCase")]
-fn save_all_preferences(app: AppHandle, preferences: Map<String, Value>) -> PreferenceResult<()> {
+fn save_all_preferences(app: AppHandle, mut preferences: Map<String, Value>) -> PreferenceResult<()> {
     let store = get_store(&app)?;
 
+    // Changed keys are stamped now; timestamps sent by the webview are ignored
+    preferences.remove(preference_merge::PREFERENCE_TIMESTAMPS_KEY);
+    let previous: Map<String, Value> = store.entries().into_iter().collect();
+    let timestamps = preference_merge::stamp_changes(&previous, &preferences, preference_merge::load_timestamps(&store));
+
+    write_all_preferences(&app, preferences, &timestamps)
+}
+
+/// Replaces the whole store with the given preferences and modification times
+fn write_all_preferences(
+    app: &AppHandle,
+    mut preferences: Map<String, Value>,
+    timestamps: &preference_merge::PreferenceTimestamps,
+) -> PreferenceResult<()> {
+    let store = get_store(app)?;
+
+    let timestamps_json = serde_json::to_value(timestamps)
+        .map_err(|e| AppError::storage(format!("Failed to serialize preference timestamps: {}", e)))?;
+    preferences.insert(preference_merge::PREFERENCE_TIMESTAMPS_KEY.to_string(), timestamps_json);
+
+    // Bulk writes always carry data in the current shape; imports are migrated before reaching here
+    preferences.insert(migrations::SCHEMA_VERSION_KEY.to_string(), json!(migrations::SCHEMA_VERSION));
+
+    preserve_backend_keys(&store, &mut preferences);
+
     // Clear existing preferences
     store.clear();
 
@@ -493,10 +632,10 @@ fn save_all_preferences(app: AppHandle, preferences: Map<String, Value>) -> Pref
     }
 
     // Save to disk
-    save_store(&store)?;
+    save_store(app, &store)?;
 
     // Emit update event to frontend
-    emit_to_frontend(&app, "preferences-updated", Value::Object(preferences))?;
+    emit_to_frontend(app, "preferences-updated", Value::Object(preferences))?;
 
     Ok(())
 }
@@ -505,7 +644,7 @@ fn save_all_preferences(app: AppHandle, preferences: Map<String, Value>) -> Pref
 #[tauri::command(rename_all = "camelCase")]
 fn get_preference(app: AppHandle, key: String) -> PreferenceResult<Value> {
     if key.trim().is_empty() {
-        return Err("Preference key cannot be empty".into());
+        return Err(AppError::invalid("key", "Preference key cannot be empty"));
     }
 
     let store = get_store(&app)?;
@@ -517,19 +656,27 @@ fn get_preference(app: AppHandle, key: String) -> PreferenceResult<Value> {
 /// Deletes a specific preference from the JSON store
 #[tauri::command(rename_all = "camelCase")]
 fn delete_preference(app: AppHandle, key: String) -> PreferenceResult<()> {
-    if key.trim().is_empty() {
-        return Err("Preference key cannot be empty".into());
+    check_writable_key(&app, &key)?;
+
+    if key == APP_META_KEY {
+        return Err(AppError::invalid("key", format!("Preference '{}' cannot be deleted directly", key)));
     }
 
     let store = get_store(&app)?;
 
     // Check if preference exists before deletion
     if !store.has(&key) {
-        return Err(format!("Preference with key '{}' does not exist", key));
+        return Err(AppError::not_found(format!("Preference with key '{}' does not exist", key)));
     }
 
     store.delete(&key);
-    save_store(&store)?;
+
+    let mut timestamps = preference_merge::load_timestamps(&store);
+    if timestamps.remove(&key).is_some() {
+        preference_merge::store_timestamps(&store, &timestamps);
+    }
+
+    save_store(&app, &store)?;
 
     // Emit deletion event to frontend
     let payload = json!({
@@ -556,13 +703,21 @@ fn get_all_preferences(app: AppHandle) -> PreferenceResult<Value> {
     Ok(Value::Object(preferences))
 }
 
-/// Clears all preferences from the JSON store
+/// Clears all preferences from the JSON store, keeping the ones owned by the backend
 #[tauri::command(rename_all = "camelCase")]
 fn clear_all_preferences(app: AppHandle) -> PreferenceResult<()> {
     let store = get_store(&app)?;
 
+    // An empty store is still in the current shape
+    let mut preferences = Map::new();
+    preferences.insert(migrations::SCHEMA_VERSION_KEY.to_string(), json!(migrations::SCHEMA_VERSION));
+    preserve_backend_keys(&store, &mut preferences);
+
     store.clear();
-    save_store(&store)?;
+    for (key, value) in preferences {
+        store.set(key, value);
+    }
+    save_store(&app, &store)?;
 
     // Emit clear event to frontend
     emit_to_frontend(&app, "preferences-cleared", Value::Null)?;
@@ -574,7 +729,7 @@ fn clear_all_preferences(app: AppHandle) -> PreferenceResult<()> {
 #[tauri::command(rename_all = "camelCase")]
 fn has_preference(app: AppHandle, key: String) -> PreferenceResult<bool> {
     if key.trim().is_empty() {
-        return Err("Preference key cannot be empty".into());
+        return Err(AppError::invalid("key", "Preference key cannot be empty"));
     }
 
     let store = get_store(&app)?;
@@ -583,6 +738,26 @@ fn has_preference(app: AppHandle, key: String) -> PreferenceResult<bool> {
     Ok(exists)
 }
 
+/// Loads the per-deck progress map, migrating the legacy single-deck key on first read
+fn load_deck_progress(app: &AppHandle) -> PreferenceResult<BTreeMap<String, VocabularyProgress>> {
+    let store = get_store(app)?;
+
+    // The legacy single-deck key is moved here by the schema migrations at startup
+    match store.get(DECK_PROGRESS_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::storage(format!("Failed to deserialize deck progress: {}", e))),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Retrieves the saved progress of a single deck, if any
+fn get_deck_progress(app: &AppHandle, directory_path: &str) -> PreferenceResult<Option<VocabularyProgress>> {
+    let deck_path = scope::scoped_deck_path(app, directory_path)?;
+    let mut deck_progress = load_deck_progress(app)?;
+
+    Ok(deck_progress.remove(deck_path.to_string_lossy().as_ref()))
+}
+
 /// Saves vocabulary learning progress with validation
 #[tauri::command(rename_all = "camelCase")]
 fn save_vocabulary_progress(
@@ -593,68 +768,130 @@ fn save_vocabulary_progress(
 ) -> PreferenceResult<()> {
     // Validate input parameters
     if directory_path.trim().is_empty() {
-        return Err("Directory path cannot be empty".into());
+        return Err(AppError::invalid("directoryPath", "Directory path cannot be empty"));
     }
 
     if current_index > total_cards {
-        return Err("Current index cannot be greater than total cards".into());
+        return Err(AppError::invalid("currentIndex", "Current index cannot be greater than total cards"));
     }
 
-    let progress = VocabularyProgress::new(current_index, total_cards, directory_path);
+    // Progress is keyed by the canonical deck path so every deck keeps its own position
+    let deck_path = scope::scoped_deck_path(&app, &directory_path)?
+        .to_string_lossy()
+        .to_string();
+
+    let mut deck_progress = load_deck_progress(&app)?;
+    deck_progress.insert(
+        deck_path.clone(),
+        VocabularyProgress::new(current_index, total_cards, deck_path),
+    );
 
     // Serialize progress to JSON
-    let progress_json = serde_json::to_value(&progress)
-        .map_err(|e| format!("Failed to serialize vocabulary progress: {}", e))?;
+    let progress_json = serde_json::to_value(&deck_progress)
+        .map_err(|e| AppError::storage(format!("Failed to serialize vocabulary progress: {}", e)))?;
 
     // Save using the existing preference system
-    save_preference(app, VOCABULARY_PROGRESS_KEY.to_string(), progress_json)
+    save_preference(app, DECK_PROGRESS_KEY.to_string(), progress_json)
 }
 
-/// Retrieves vocabulary learning progress
+/// Retrieves vocabulary learning progress of a deck (null if it was never studied)
 #[tauri::command(rename_all = "camelCase")]
-fn get_vocabulary_progress(app: AppHandle) -> PreferenceResult<Value> {
-    get_preference(app, VOCABULARY_PROGRESS_KEY.to_string())
+fn get_vocabulary_progress(app: AppHandle, directory_path: String) -> PreferenceResult<Value> {
+    let progress = get_deck_progress(&app, &directory_path)?;
+
+    serde_json::to_value(&progress)
+        .map_err(|e| AppError::storage(format!("Failed to serialize vocabulary progress: {}", e)))
 }
 
 /// Validates and exports preferences to a JSON file
 #[tauri::command(rename_all = "camelCase")]
 fn export_preferences(app: AppHandle, file_path: String) -> PreferenceResult<()> {
     if file_path.trim().is_empty() {
-        return Err("Export file path cannot be empty".into());
+        return Err(AppError::invalid("filePath", "Export file path cannot be empty"));
     }
 
+    let path = scope::check_path(&app, &file_path)?;
     let preferences = get_all_preferences(app)?;
 
     // Write preferences to file
     let json_string = serde_json::to_string_pretty(&preferences)
-        .map_err(|e| format!("Failed to serialize preferences: {}", e))?;
+        .map_err(|e| AppError::storage(format!("Failed to serialize preferences: {}", e)))?;
 
-    fs::write(&file_path, json_string)
-        .map_err(|e| format!("Failed to write preferences to file '{}': {}", file_path, e))?;
+    fs::write(&path, json_string)
+        .map_err(|e| AppError::io(&path, format!("Failed to write preferences to file '{}': {}", file_path, e)))?;
 
     Ok(())
 }
 
-/// Imports preferences from a JSON file with validation
+/// Imports preferences from a JSON file with validation, combining them with the
+/// local preferences according to `strategy` (replacing them when omitted)
 #[tauri::command(rename_all = "camelCase")]
-fn import_preferences(app: AppHandle, file_path: String) -> PreferenceResult<()> {
+fn import_preferences(
+    app: AppHandle,
+    file_path: String,
+    strategy: Option<preference_merge::MergeStrategy>,
+) -> PreferenceResult<preference_merge::ImportReport> {
     if file_path.trim().is_empty() {
-        return Err("Import file path cannot be empty".into());
+        return Err(AppError::invalid("filePath", "Import file path cannot be empty"));
     }
 
     // Read and parse JSON file
-    let file_content = fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read preferences file '{}': {}", file_path, e))?;
+    let path = scope::check_path(&app, &file_path)?;
+    let file_content = fs::read_to_string(&path)
+        .map_err(|e| AppError::io(&path, format!("Failed to read preferences file '{}': {}", file_path, e)))?;
+
+    let mut preferences: Map<String, Value> = serde_json::from_str(&file_content)
+        .map_err(|e| AppError::parse(&path, Some(e.line()), format!("Failed to parse JSON from file '{}': {}", file_path, e)))?;
+
+    // Files exported by older versions are brought to the current shape first
+    migrations::migrate(&mut preferences)?;
 
-    let preferences: Map<String, Value> = serde_json::from_str(&file_content)
-        .map_err(|e| format!("Failed to parse JSON from file '{}': {}", file_path, e))?;
+    // Values the app relies on are checked before anything is written
+    for (key, value) in &preferences {
+        data_health::check_shape(key, value)
+            .map_err(|reason| AppError::parse(&path, None, format!("Invalid '{}' in '{}': {}", key, file_path, reason)))?;
+    }
+
+    let local = match get_all_preferences(app.clone())? {
+        Value::Object(local) => local,
+        _ => Map::new(),
+    };
+
+    let (merged, timestamps, report) = preference_merge::merge(local, preferences, strategy.unwrap_or_default());
+
+    // The preferences before the import can be restored from the backups
+    storage::create_backup(&app)?;
+    write_all_preferences(&app, merged, &timestamps)?;
 
-    // Import preferences using bulk save
-    save_all_preferences(app, preferences)
+    Ok(report)
 }
 
-/// Initializes the store and loads initial data
+/// Initializes the profiles and loads the profile reopened at startup, if any
 fn initialize_store(app: &AppHandle) -> PreferenceResult<()> {
+    if profiles::initialize_profiles(app)? {
+        load_profile_store(app)?;
+    }
+
+    Ok(())
+}
+
+/// Loads the active profile's store and sends its data to the frontend
+fn load_profile_store(app: &AppHandle) -> PreferenceResult<()> {
+    // Replace a corrupt store with its newest valid backup before loading it
+    storage::recover_store(app)?;
+
+    // Upgrade older data shapes; a store that failed to migrate is still loaded, read-only
+    let migrated = migrations::upgrade_store(app)?;
+
+    // Launches are counted at startup and the first time each profile is opened, not on every switch back
+    if migrated && profiles::mark_active_opened(app)? {
+        match record_launch(app) {
+            // Unreadable metadata was already reported when it was quarantined; the launch is not counted
+            Err(AppError::Quarantined { .. }) => {}
+            result => result?,
+        }
+    }
+
     let store = get_store(app)?;
 
     // Load user data (if exists)
@@ -680,6 +917,10 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_store::Builder::default().build())
+        .manage(search::SearchState::default())
+        .manage(games::GameState::default())
+        .manage(profiles::ProfileState::default())
+        .manage(migrations::MigrationState::default())
         .setup(|app| {
             // Initialize store and load initial data
             if let Err(e) = initialize_store(&app.handle()) {
@@ -689,6 +930,14 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
+            // Learner profiles
+            profiles::list_profiles,
+            profiles::get_active_profile,
+            profiles::create_profile,
+            profiles::switch_profile,
+            profiles::set_profile_pin,
+            profiles::delete_profile,
+
             // Profile management
             create_or_update_profile,
             get_profile_data,
@@ -701,6 +950,83 @@ pub fn run() {
             list_directory_contents,
             extract_vocabulary_fields,
 
+            // Deck index
+            index::open_deck,
+            index::rebuild_index,
+
+            // Card authoring
+            authoring::create_card,
+            authoring::update_card,
+            authoring::delete_card,
+
+            // Duplicate detection
+            duplicates::find_duplicates,
+            duplicates::merge_duplicates,
+
+            // Scheduling and search
+            scheduling::review_card,
+            scheduling::get_card_schedules,
+            search::search_cards,
+            cloze::get_cloze_cards,
+            conjugation::get_conjugation,
+            drill::get_conjugation_drill,
+            drill::answer_conjugation_drill,
+            articles::get_noun_forms,
+            articles::get_article_drill,
+            articles::answer_article_drill,
+            quiz::generate_quiz,
+
+            // Games
+            games::start_match_round,
+            games::submit_match,
+            games::start_sprint,
+            games::answer_sprint,
+            games::finish_sprint,
+            games::get_high_scores,
+
+            // Filtered decks and study sessions
+            filtered_decks::save_filtered_deck,
+            filtered_decks::delete_filtered_deck,
+            filtered_decks::list_filtered_decks,
+            filtered_decks::preview_filtered_deck,
+            study::start_study_session,
+
+            // Recent decks
+            decks::restore_last_session,
+            decks::list_decks,
+            decks::list_recent_decks,
+            decks::pin_recent_deck,
+            decks::rename_recent_deck,
+            decks::forget_recent_deck,
+
+            // Git repositories
+            repository::open_deck_repository,
+            repository::clone_deck_repository,
+            repository::pull_deck_repository,
+            repository::get_repository_status,
+            repository::commit_deck_changes,
+            repository::get_card_history,
+
+            // File access scopes
+            scope::pick_directory,
+            scope::pick_import_file,
+            scope::pick_export_file,
+            scope::list_allowed_scopes,
+            scope::revoke_scope,
+
+            // Store backups
+            storage::list_backups,
+            storage::restore_backup,
+
+            // Data health
+            data_health::get_data_health,
+            data_health::resolve_quarantined_value,
+
+            // Profile archives
+            profile_archive::export_profile_archive,
+            profile_archive::preview_profile_archive,
+            profile_archive::import_profile_archive,
+
             // Preference management
             save_preference,
             save_all_preferences,
@@ -0,0 +1,240 @@
+// migrations.rs - Ordered upgrades of stored data shapes, keyed by a schema version
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::index;
+use crate::storage;
+use crate::{
+    emit_to_frontend, get_store, save_store, PreferenceResult, VocabularyProgress, DECK_PROGRESS_KEY,
+    VOCABULARY_PROGRESS_KEY,
+};
+
+/// Store key holding the schema version of the stored data. Only written by the backend.
+pub(crate) const SCHEMA_VERSION_KEY: &str = "schemaVersion";
+
+/// Failure of the last migration of the active profile's store. While one is recorded,
+/// store writes are refused so data in an older shape is never stamped with the current version.
+#[derive(Default)]
+pub struct MigrationState {
+    failure: Mutex<Option<String>>,
+}
+
+/// Upgrades preferences by one schema version, failing with a reason instead of dropping data
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// Migrations in order: the one at index `n` upgrades schema version `n` to `n + 1`.
+/// Stores written before schema versions existed are version 0. Append only.
+const MIGRATIONS: &[(&str, Migration)] = &[
+    ("move single-deck vocabulary progress into per-deck progress", move_legacy_vocabulary_progress),
+];
+
+/// Schema version of data written by this build
+pub(crate) const SCHEMA_VERSION: u64 = MIGRATIONS.len() as u64;
+
+/// Moves the legacy global vocabulary progress under its deck, unless that deck already has newer data
+fn move_legacy_vocabulary_progress(preferences: &mut Map<String, Value>) -> Result<(), String> {
+    let Some(legacy_value) = preferences.remove(VOCABULARY_PROGRESS_KEY) else {
+        return Ok(());
+    };
+
+    let mut legacy_progress: VocabularyProgress = serde_json::from_value(legacy_value)
+        .map_err(|e| format!("legacy vocabulary progress is unreadable: {}", e))?;
+
+    let mut deck_progress: BTreeMap<String, VocabularyProgress> = match preferences.remove(DECK_PROGRESS_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| format!("deck progress is unreadable: {}", e))?,
+        None => BTreeMap::new(),
+    };
+
+    if let Ok(canonical_path) = index::canonical_deck_path(&legacy_progress.directory_path) {
+        legacy_progress.directory_path = canonical_path.to_string_lossy().to_string();
+    }
+
+    deck_progress.entry(legacy_progress.directory_path.clone())
+        .or_insert(legacy_progress);
+
+    let progress_json = serde_json::to_value(&deck_progress)
+        .map_err(|e| format!("failed to serialize deck progress: {}", e))?;
+    preferences.insert(DECK_PROGRESS_KEY.to_string(), progress_json);
+
+    Ok(())
+}
+
+/// Schema version recorded in a set of preferences
+fn schema_version(preferences: &Map<String, Value>) -> PreferenceResult<u64> {
+    match preferences.get(SCHEMA_VERSION_KEY) {
+        None => Ok(0),
+        Some(value) => value.as_u64()
+            .ok_or_else(|| AppError::storage(format!("Schema version '{}' is not a number", value))),
+    }
+}
+
+/// Brings preferences from any older schema to the current one. Returns the
+/// version they started from. Newer schemas are refused, since this build
+/// cannot know their shape.
+pub(crate) fn migrate(preferences: &mut Map<String, Value>) -> PreferenceResult<u64> {
+    let version = schema_version(preferences)?;
+
+    if version > SCHEMA_VERSION {
+        return Err(AppError::storage(format!(
+            "The data has schema version {}, newer than version {} supported by this app; update the app to open it",
+            version, SCHEMA_VERSION
+        )));
+    }
+
+    // Migrations run on a copy so a failure leaves the preferences as they were
+    let mut migrated = preferences.clone();
+
+    for (index, (description, migration)) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut migrated).map_err(|reason| {
+            AppError::storage(format!("Migration to schema version {} ({}) failed: {}", index + 1, description, reason))
+        })?;
+    }
+
+    migrated.insert(SCHEMA_VERSION_KEY.to_string(), json!(SCHEMA_VERSION));
+    *preferences = migrated;
+
+    Ok(version)
+}
+
+/// Migrates the active profile's store to the current schema. The store is
+/// backed up first and only rewritten once every migration succeeded.
+fn migrate_store(app: &AppHandle) -> PreferenceResult<()> {
+    let store = get_store(app)?;
+    let mut preferences: Map<String, Value> = store.entries().into_iter().collect();
+
+    if schema_version(&preferences)? == SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    // A new store has nothing to migrate and starts at the current version
+    if preferences.is_empty() {
+        store.set(SCHEMA_VERSION_KEY, json!(SCHEMA_VERSION));
+        return save_store(app, &store);
+    }
+
+    let backup = storage::create_backup(app)?;
+    let from_version = migrate(&mut preferences)?;
+
+    store.clear();
+    for (key, value) in preferences {
+        store.set(key, value);
+    }
+    save_store(app, &store)?;
+
+    let payload = json!({
+        "fromVersion": from_version,
+        "toVersion": SCHEMA_VERSION,
+        "backup": backup.as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+    });
+    emit_to_frontend(app, "store-migrated", payload)
+}
+
+fn set_failure(app: &AppHandle, reason: Option<String>) -> PreferenceResult<()> {
+    let state = app.state::<MigrationState>();
+    let mut failure = state.failure.lock()
+        .map_err(|_| AppError::internal("Migration state lock was poisoned"))?;
+    *failure = reason;
+
+    Ok(())
+}
+
+/// Refuses store writes while the active profile's store awaits a migration that failed
+pub(crate) fn ensure_migrated(app: &AppHandle) -> PreferenceResult<()> {
+    let state = app.state::<MigrationState>();
+    let failure = state.failure.lock()
+        .map_err(|_| AppError::internal("Migration state lock was poisoned"))?;
+
+    match failure.as_ref() {
+        Some(reason) => Err(AppError::storage(format!(
+            "Changes cannot be saved until the stored data is upgraded. {}", reason
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Migrates the active profile's store and returns whether it now has the current
+/// schema. A failure is reported to the frontend through `store-migration-failed`;
+/// the store stays readable but refuses writes until a later migration succeeds.
+pub(crate) fn upgrade_store(app: &AppHandle) -> PreferenceResult<bool> {
+    set_failure(app, None)?;
+
+    let Err(error) = migrate_store(app) else {
+        return Ok(true);
+    };
+
+    set_failure(app, Some(error.message().to_string()))?;
+
+    let payload = serde_json::to_value(&error)
+        .map_err(|e| AppError::internal(format!("Failed to serialize migration error: {}", e)))?;
+    emit_to_frontend(app, "store-migration-failed", payload)?;
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_progress(directory_path: &str) -> Value {
+        json!({
+            "current_index": 3,
+            "total_cards": 10,
+            "directory_path": directory_path,
+            "last_updated": 1_700_000_000
+        })
+    }
+
+    #[test]
+    fn migrates_unversioned_preferences_to_the_current_schema() {
+        let mut preferences = Map::new();
+        preferences.insert(VOCABULARY_PROGRESS_KEY.to_string(), legacy_progress("/decks/missing-italian"));
+
+        assert_eq!(migrate(&mut preferences).unwrap(), 0);
+
+        assert_eq!(preferences[SCHEMA_VERSION_KEY], json!(SCHEMA_VERSION));
+        assert!(!preferences.contains_key(VOCABULARY_PROGRESS_KEY));
+        assert_eq!(preferences[DECK_PROGRESS_KEY]["/decks/missing-italian"]["current_index"], 3);
+    }
+
+    #[test]
+    fn keeps_newer_deck_progress_over_the_legacy_one() {
+        let mut preferences = Map::new();
+        preferences.insert(VOCABULARY_PROGRESS_KEY.to_string(), legacy_progress("/decks/missing-italian"));
+        let mut newer = legacy_progress("/decks/missing-italian");
+        newer["current_index"] = json!(7);
+        preferences.insert(DECK_PROGRESS_KEY.to_string(), json!({ "/decks/missing-italian": newer }));
+
+        migrate(&mut preferences).unwrap();
+
+        assert_eq!(preferences[DECK_PROGRESS_KEY]["/decks/missing-italian"]["current_index"], 7);
+    }
+
+    #[test]
+    fn refuses_a_newer_schema() {
+        let mut preferences = Map::new();
+        preferences.insert(SCHEMA_VERSION_KEY.to_string(), json!(SCHEMA_VERSION + 1));
+        let original = preferences.clone();
+
+        assert!(migrate(&mut preferences).is_err());
+        assert_eq!(preferences, original);
+    }
+
+    #[test]
+    fn leaves_the_preferences_untouched_when_a_migration_fails() {
+        let mut preferences = Map::new();
+        preferences.insert(VOCABULARY_PROGRESS_KEY.to_string(), json!("not progress"));
+        preferences.insert("theme".to_string(), json!("dark"));
+        let original = preferences.clone();
+
+        let error = migrate(&mut preferences).unwrap_err();
+
+        assert!(error.message().contains("legacy vocabulary progress is unreadable"));
+        assert_eq!(preferences, original);
+    }
+}
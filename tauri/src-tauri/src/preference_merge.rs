@@ -0,0 +1,159 @@
+// preference_merge.rs - Per-key modification times and merge strategies for preference imports
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use chrono::{DateTime, Utc};
+
+use crate::data_health::QUARANTINE_KEY;
+use crate::migrations::SCHEMA_VERSION_KEY;
+use crate::scope;
+use crate::APP_META_KEY;
+
+/// Store key holding when each preference was last changed. Only written by the backend.
+pub(crate) const PREFERENCE_TIMESTAMPS_KEY: &str = "preferenceTimestamps";
+
+/// Last modification time of each preference key
+pub(crate) type PreferenceTimestamps = BTreeMap<String, DateTime<Utc>>;
+
+/// How an imported preferences file is combined with the local preferences
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStrategy {
+    /// Replaces every local preference with the imported ones
+    #[default]
+    Replace,
+    /// Keeps local values and only adds keys missing locally
+    KeepLocal,
+    /// Takes imported values for every key in the file, keeping local-only keys
+    TakeImported,
+    /// Takes whichever value was changed last; keys without a timestamp lose to those with one
+    NewestWins,
+}
+
+/// Keys affected by an import
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Imported keys that did not exist locally
+    pub added: Vec<String>,
+    /// Local keys overwritten by a different imported value
+    pub changed: Vec<String>,
+    /// Imported keys whose different value was not applied
+    pub skipped: Vec<String>,
+    /// Local keys dropped because they were missing from the file
+    pub removed: Vec<String>,
+}
+
+/// Reads the modification times from a preference map. Unreadable timestamps are ignored,
+/// which only makes the affected keys lose newest-wins comparisons.
+pub(crate) fn timestamps_of(preferences: &Map<String, Value>) -> PreferenceTimestamps {
+    preferences.get(PREFERENCE_TIMESTAMPS_KEY)
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Reads the modification times from the store
+pub(crate) fn load_timestamps(store: &Arc<tauri_plugin_store::Store<tauri::Wry>>) -> PreferenceTimestamps {
+    store.get(PREFERENCE_TIMESTAMPS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Writes the modification times to the store, without saving it
+pub(crate) fn store_timestamps(store: &Arc<tauri_plugin_store::Store<tauri::Wry>>, timestamps: &PreferenceTimestamps) {
+    if let Ok(value) = serde_json::to_value(timestamps) {
+        store.set(PREFERENCE_TIMESTAMPS_KEY, value);
+    }
+}
+
+/// Updates the modification times after a bulk save: changed and new keys
+/// are stamped now, dropped keys are forgotten
+pub(crate) fn stamp_changes(
+    previous: &Map<String, Value>,
+    preferences: &Map<String, Value>,
+    mut timestamps: PreferenceTimestamps,
+) -> PreferenceTimestamps {
+    let now = Utc::now();
+
+    timestamps.retain(|key, _| preferences.contains_key(key));
+    for (key, value) in preferences {
+        if previous.get(key) != Some(value) || !timestamps.contains_key(key) {
+            timestamps.insert(key.clone(), now);
+        }
+    }
+
+    timestamps
+}
+
+/// Whether a key is managed by the backend and never taken from an import
+fn is_internal(key: &str) -> bool {
+    [scope::ALLOWED_SCOPES_KEY, PREFERENCE_TIMESTAMPS_KEY, SCHEMA_VERSION_KEY, QUARANTINE_KEY, APP_META_KEY].contains(&key)
+}
+
+/// Combines imported preferences with the local ones. Returns the resulting
+/// preferences, their modification times and a report of what changed.
+pub(crate) fn merge(
+    mut local: Map<String, Value>,
+    mut imported: Map<String, Value>,
+    strategy: MergeStrategy,
+) -> (Map<String, Value>, PreferenceTimestamps, ImportReport) {
+    let mut local_timestamps = timestamps_of(&local);
+    let imported_timestamps = timestamps_of(&imported);
+    local.retain(|key, _| !is_internal(key));
+    imported.retain(|key, _| !is_internal(key));
+
+    let mut report = ImportReport::default();
+    let now = Utc::now();
+
+    if strategy == MergeStrategy::Replace {
+        report.removed = local.keys()
+            .filter(|key| !imported.contains_key(*key))
+            .cloned()
+            .collect();
+        local.retain(|key, _| imported.contains_key(key));
+    }
+
+    for (key, imported_value) in imported {
+        let imported_at = imported_timestamps.get(&key).copied();
+
+        let take_imported = match local.get(&key) {
+            None => {
+                report.added.push(key.clone());
+                true
+            }
+            Some(local_value) if *local_value == imported_value => false,
+            Some(_) => {
+                let take = match strategy {
+                    MergeStrategy::Replace | MergeStrategy::TakeImported => true,
+                    MergeStrategy::KeepLocal => false,
+                    MergeStrategy::NewestWins => match (imported_at, local_timestamps.get(&key)) {
+                        (Some(imported_at), Some(local_at)) => imported_at > *local_at,
+                        (Some(_), None) => true,
+                        (None, _) => false,
+                    },
+                };
+
+                if take {
+                    report.changed.push(key.clone());
+                } else {
+                    report.skipped.push(key.clone());
+                }
+                take
+            }
+        };
+
+        if take_imported {
+            // The imported time is kept so later newest-wins imports compare real edit times
+            local_timestamps.insert(key.clone(), imported_at.unwrap_or(now));
+            local.insert(key, imported_value);
+        }
+    }
+
+    local_timestamps.retain(|key, _| local.contains_key(key));
+    for key in local.keys() {
+        local_timestamps.entry(key.clone()).or_insert(now);
+    }
+
+    (local, local_timestamps, report)
+}
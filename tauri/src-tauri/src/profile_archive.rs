@@ -0,0 +1,471 @@
+// profile_archive.rs - Versioned zip archives of the whole learning profile
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+use chrono::{DateTime, Utc};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::data_health;
+use crate::error::AppError;
+use crate::index::content_hash;
+use crate::migrations;
+use crate::preference_merge::PREFERENCE_TIMESTAMPS_KEY;
+use crate::profiles;
+use crate::scheduling::{self, DeckSchedules, ReviewLog};
+use crate::scope;
+use crate::storage;
+use crate::validation;
+use crate::{
+    get_all_preferences, save_all_preferences, PreferenceResult, ProfileUser, VocabularyProgress, APP_META_KEY,
+    DECK_PROGRESS_KEY, PROFILE_USER_KEY, PROGRESS_KEY,
+};
+
+/// Identifies our archives among other zip files
+const ARCHIVE_FORMAT: &str = "percorso-profile";
+
+/// Version of the archive layout. Archives from newer versions are refused.
+const ARCHIVE_VERSION: u32 = 1;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Holds every key not listed in `SECTIONS`
+const PREFERENCES_FILE_NAME: &str = "preferences.json";
+
+/// Entries larger than this are refused rather than decompressed
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Archive files and the store keys they hold
+const SECTIONS: &[(&str, &[&str])] = &[
+    ("profile.json", &[PROFILE_USER_KEY, PROGRESS_KEY]),
+    ("progress.json", &[DECK_PROGRESS_KEY]),
+    ("scheduling.json", &[scheduling::CARD_SCHEDULES_KEY]),
+    ("history.json", &[scheduling::REVIEW_HISTORY_KEY]),
+];
+
+/// Describes an archive and the SHA-256 of each file in it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub files: BTreeMap<String, String>,
+    /// SHA-256 over the file names and hashes, guarding the manifest itself
+    pub checksum: String,
+}
+
+/// How an archive is applied to the local profile
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportMode {
+    /// Keeps local data, adding what is missing and combining schedules, history and progress
+    Merge,
+    /// Replaces the local profile with the archive
+    Replace,
+}
+
+/// What importing would do to a store key
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyStatus {
+    /// Only in the archive
+    Added,
+    /// In both, with different values
+    Changed,
+    /// In both, with equal values
+    Unchanged,
+    /// Only local; dropped when replacing, kept when merging
+    Removed,
+}
+
+/// Difference between the archive and the local profile for one key
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyChange {
+    pub key: String,
+    pub file: String,
+    pub status: KeyStatus,
+}
+
+/// Summary shown before an archive is imported
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivePreview {
+    pub manifest: ArchiveManifest,
+    pub changes: Vec<KeyChange>,
+    pub scheduled_cards: usize,
+    pub reviews: usize,
+    pub local_scheduled_cards: usize,
+    pub local_reviews: usize,
+}
+
+/// A validated archive
+struct ArchiveContents {
+    manifest: ArchiveManifest,
+    preferences: Map<String, Value>,
+}
+
+/// Archive file holding a store key
+fn section_of(key: &str) -> &'static str {
+    SECTIONS.iter()
+        .find(|(_, keys)| keys.contains(&key))
+        .map_or(PREFERENCES_FILE_NAME, |(file_name, _)| file_name)
+}
+
+/// Checksum of the manifest's file list
+fn manifest_checksum(files: &BTreeMap<String, String>) -> String {
+    let listing: String = files.iter()
+        .map(|(file_name, hash)| format!("{}:{}\n", file_name, hash))
+        .collect();
+
+    content_hash(listing.as_bytes())
+}
+
+/// Whether a key is kept by the backend for this machine and left out of archives.
+/// Modification times are restamped when an archive is imported.
+fn is_local_only(key: &str) -> bool {
+    key == scope::ALLOWED_SCOPES_KEY
+        || key == PREFERENCE_TIMESTAMPS_KEY
+        || key == data_health::QUARANTINE_KEY
+        || key == APP_META_KEY
+}
+
+/// Current store contents, without the machine-specific keys
+fn local_preferences(app: &AppHandle) -> PreferenceResult<Map<String, Value>> {
+    let mut preferences = match get_all_preferences(app.clone())? {
+        Value::Object(preferences) => preferences,
+        _ => Map::new(),
+    };
+    preferences.retain(|key, _| !is_local_only(key));
+
+    Ok(preferences)
+}
+
+/// Validates the keys whose shape the app relies on
+fn validate_key(file_name: &str, key: &str, value: &Value) -> PreferenceResult<()> {
+    data_health::check_shape(key, value)
+        .map_err(|reason| AppError::parse(file_name, None, format!("Invalid '{}' in {}: {}", key, file_name, reason)))
+}
+
+/// Reads one archive entry, refusing oversized ones
+fn read_entry(archive: &mut ZipArchive<File>, file_name: &str) -> PreferenceResult<Vec<u8>> {
+    let entry = archive.by_name(file_name)
+        .map_err(|e| AppError::parse(file_name, None, format!("Archive entry '{}' is missing: {}", file_name, e)))?;
+
+    if entry.size() > MAX_ENTRY_BYTES {
+        return Err(AppError::parse(file_name, None, format!("Archive entry '{}' is too large", file_name)));
+    }
+
+    let mut bytes = Vec::new();
+    entry.take(MAX_ENTRY_BYTES).read_to_end(&mut bytes)
+        .map_err(|e| AppError::parse(file_name, None, format!("Failed to read archive entry '{}': {}", file_name, e)))?;
+
+    Ok(bytes)
+}
+
+/// Opens an archive and checks its manifest, checksums and data shapes
+fn read_archive(path: &Path) -> PreferenceResult<ArchiveContents> {
+    let file = File::open(path)
+        .map_err(|e| AppError::io(path, format!("Failed to open archive '{}': {}", path.display(), e)))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| AppError::parse(path, None, format!("'{}' is not a valid zip archive: {}", path.display(), e)))?;
+
+    let manifest: ArchiveManifest = serde_json::from_slice(&read_entry(&mut archive, MANIFEST_FILE_NAME)?)
+        .map_err(|e| AppError::parse(MANIFEST_FILE_NAME, Some(e.line()), format!("Invalid archive manifest: {}", e)))?;
+
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(AppError::invalid("filePath", "The file is not a learning profile archive"));
+    }
+
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(AppError::invalid(
+            "filePath",
+            format!("The archive has version {}; this app reads up to version {}", manifest.version, ARCHIVE_VERSION),
+        ));
+    }
+
+    if manifest_checksum(&manifest.files) != manifest.checksum {
+        return Err(AppError::parse(MANIFEST_FILE_NAME, None, "The archive manifest does not match its checksum"));
+    }
+
+    let mut preferences = Map::new();
+
+    for (file_name, expected_hash) in &manifest.files {
+        let bytes = read_entry(&mut archive, file_name)?;
+
+        if content_hash(&bytes) != *expected_hash {
+            return Err(AppError::parse(file_name, None, format!("Archive entry '{}' is damaged", file_name)));
+        }
+
+        let values: Map<String, Value> = serde_json::from_slice(&bytes)
+            .map_err(|e| AppError::parse(file_name, Some(e.line()), format!("Invalid archive entry '{}': {}", file_name, e)))?;
+
+        preferences.extend(values.into_iter().filter(|(key, _)| !is_local_only(key)));
+    }
+
+    // Archives from older versions are brought to the current shape before their data is checked
+    migrations::migrate(&mut preferences)?;
+
+    for (key, value) in &preferences {
+        validate_key(section_of(key), key, value)?;
+    }
+
+    Ok(ArchiveContents { manifest, preferences })
+}
+
+/// Number of scheduled cards and logged reviews in a set of preferences
+fn study_counts(preferences: &Map<String, Value>) -> (usize, usize) {
+    let scheduled_cards = preferences.get(scheduling::CARD_SCHEDULES_KEY)
+        .and_then(Value::as_object)
+        .map_or(0, |decks| decks.values().filter_map(Value::as_object).map(Map::len).sum());
+
+    let reviews = preferences.get(scheduling::REVIEW_HISTORY_KEY)
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len);
+
+    (scheduled_cards, reviews)
+}
+
+/// Compares an archive with the local profile
+fn preview_of(local: &Map<String, Value>, contents: &ArchiveContents) -> ArchivePreview {
+    let mut changes: Vec<KeyChange> = contents.preferences.iter()
+        .map(|(key, value)| KeyChange {
+            key: key.clone(),
+            file: section_of(key).to_string(),
+            status: match local.get(key) {
+                None => KeyStatus::Added,
+                Some(local_value) if local_value == value => KeyStatus::Unchanged,
+                Some(_) => KeyStatus::Changed,
+            },
+        })
+        .collect();
+
+    changes.extend(local.keys()
+        .filter(|key| !contents.preferences.contains_key(*key))
+        .map(|key| KeyChange {
+            key: key.clone(),
+            file: section_of(key).to_string(),
+            status: KeyStatus::Removed,
+        }));
+
+    changes.sort_by(|change_a, change_b| change_a.file.cmp(&change_b.file).then(change_a.key.cmp(&change_b.key)));
+
+    let (scheduled_cards, reviews) = study_counts(&contents.preferences);
+    let (local_scheduled_cards, local_reviews) = study_counts(local);
+
+    ArchivePreview {
+        manifest: contents.manifest.clone(),
+        changes,
+        scheduled_cards,
+        reviews,
+        local_scheduled_cards,
+        local_reviews,
+    }
+}
+
+/// Merges two values of a known shape, falling back to whichever side can be read
+fn merge_typed<T: DeserializeOwned + Serialize>(local: &Value, imported: &Value, merge: impl FnOnce(T, T) -> T) -> Value {
+    match (serde_json::from_value::<T>(local.clone()), serde_json::from_value::<T>(imported.clone())) {
+        (Ok(local_data), Ok(imported_data)) => serde_json::to_value(merge(local_data, imported_data))
+            .unwrap_or_else(|_| local.clone()),
+        (Err(_), _) => imported.clone(),
+        (_, Err(_)) => local.clone(),
+    }
+}
+
+/// Keeps the most recently reviewed schedule of every card
+fn merge_schedules(
+    mut local: BTreeMap<String, DeckSchedules>,
+    imported: BTreeMap<String, DeckSchedules>,
+) -> BTreeMap<String, DeckSchedules> {
+    for (deck_path, imported_schedules) in imported {
+        let deck_schedules = local.entry(deck_path).or_default();
+
+        for (card_id, schedule) in imported_schedules {
+            let newer = deck_schedules.get(&card_id)
+                .map_or(true, |local_schedule| schedule.last_reviewed > local_schedule.last_reviewed);
+
+            if newer {
+                deck_schedules.insert(card_id, schedule);
+            }
+        }
+    }
+
+    local
+}
+
+/// Combines both review logs in time order, dropping reviews present in both
+fn merge_history(mut local: Vec<ReviewLog>, imported: Vec<ReviewLog>) -> Vec<ReviewLog> {
+    local.extend(imported);
+    local.sort_by(|review_a, review_b| {
+        review_a.reviewed_at.cmp(&review_b.reviewed_at)
+            .then_with(|| review_a.deck_path.cmp(&review_b.deck_path))
+            .then_with(|| review_a.card_id.cmp(&review_b.card_id))
+    });
+    local.dedup_by(|review_a, review_b| {
+        review_a.reviewed_at == review_b.reviewed_at
+            && review_a.deck_path == review_b.deck_path
+            && review_a.card_id == review_b.card_id
+    });
+    scheduling::trim_review_history(&mut local);
+
+    local
+}
+
+/// Keeps the most recently updated progress of every deck
+fn merge_deck_progress(
+    mut local: BTreeMap<String, VocabularyProgress>,
+    imported: BTreeMap<String, VocabularyProgress>,
+) -> BTreeMap<String, VocabularyProgress> {
+    for (deck_path, progress) in imported {
+        let newer = local.get(&deck_path)
+            .map_or(true, |local_progress| progress.last_updated > local_progress.last_updated);
+
+        if newer {
+            local.insert(deck_path, progress);
+        }
+    }
+
+    local
+}
+
+/// Adds imported keys missing locally and combines study data; other local values win
+fn merge_preferences(mut local: Map<String, Value>, imported: Map<String, Value>) -> Map<String, Value> {
+    for (key, imported_value) in imported {
+        let merged = match local.get(&key) {
+            None => imported_value,
+            Some(local_value) => match key.as_str() {
+                scheduling::CARD_SCHEDULES_KEY => merge_typed(local_value, &imported_value, merge_schedules),
+                scheduling::REVIEW_HISTORY_KEY => merge_typed(local_value, &imported_value, merge_history),
+                DECK_PROGRESS_KEY => merge_typed(local_value, &imported_value, merge_deck_progress),
+                _ => continue,
+            },
+        };
+
+        local.insert(key, merged);
+    }
+
+    local
+}
+
+/// Checks an imported user like one entered in the app, including the uniqueness of its
+/// username across the other profiles, and keeps its normalized fields
+fn validate_imported_user(app: &AppHandle, preferences: &mut Map<String, Value>) -> PreferenceResult<()> {
+    let Some(user_value) = preferences.get_mut(PROFILE_USER_KEY) else {
+        return Ok(());
+    };
+
+    let mut user: ProfileUser = serde_json::from_value(user_value.clone())
+        .map_err(|e| AppError::parse("profile.json", None, format!("Invalid '{}' in profile.json: {}", PROFILE_USER_KEY, e)))?;
+
+    let taken_usernames = profiles::other_usernames(app)?;
+    let fields = validation::validate_profile(&user.full_name, &user.username, &user.email, &taken_usernames)?;
+    user.full_name = fields.full_name;
+    user.username = fields.username;
+    user.email = fields.email;
+
+    *user_value = serde_json::to_value(&user)
+        .map_err(|e| AppError::storage(format!("Failed to serialize profile: {}", e)))?;
+
+    Ok(())
+}
+
+/// Writes the profile, progress, schedules, review history and preferences
+/// to a zip archive with a manifest of checksums
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn export_profile_archive(app: AppHandle, file_path: String) -> PreferenceResult<ArchiveManifest> {
+    let path = scope::check_path(&app, &file_path)?;
+
+    // Every section is written, even when empty, so imports can tell "none" from "missing"
+    let mut sections: BTreeMap<&str, Map<String, Value>> = SECTIONS.iter()
+        .map(|(file_name, _)| *file_name)
+        .chain([PREFERENCES_FILE_NAME])
+        .map(|file_name| (file_name, Map::new()))
+        .collect();
+
+    for (key, value) in local_preferences(&app)? {
+        sections.entry(section_of(&key)).or_default().insert(key, value);
+    }
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut write_entry = |file_name: &str, bytes: &[u8]| -> PreferenceResult<()> {
+        writer.start_file(file_name, options)
+            .map_err(|e| AppError::internal(format!("Failed to add '{}' to the archive: {}", file_name, e)))?;
+        writer.write_all(bytes)
+            .map_err(|e| AppError::internal(format!("Failed to add '{}' to the archive: {}", file_name, e)))
+    };
+
+    let mut files = BTreeMap::new();
+    for (file_name, values) in &sections {
+        let bytes = serde_json::to_vec_pretty(values)
+            .map_err(|e| AppError::internal(format!("Failed to serialize '{}': {}", file_name, e)))?;
+
+        write_entry(file_name, &bytes)?;
+        files.insert(file_name.to_string(), content_hash(&bytes));
+    }
+
+    let manifest = ArchiveManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        app_version: app.package_info().version.to_string(),
+        created_at: Utc::now(),
+        checksum: manifest_checksum(&files),
+        files,
+    };
+
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| AppError::internal(format!("Failed to serialize archive manifest: {}", e)))?;
+    write_entry(MANIFEST_FILE_NAME, &manifest_bytes)?;
+
+    let archive_bytes = writer.finish()
+        .map_err(|e| AppError::internal(format!("Failed to finish archive: {}", e)))?
+        .into_inner();
+
+    storage::write_atomic(&path, &archive_bytes)?;
+
+    Ok(manifest)
+}
+
+/// Validates an archive and shows how it differs from the local profile
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn preview_profile_archive(app: AppHandle, file_path: String) -> PreferenceResult<ArchivePreview> {
+    let path = scope::check_path(&app, &file_path)?;
+    let contents = read_archive(&path)?;
+
+    Ok(preview_of(&local_preferences(&app)?, &contents))
+}
+
+/// Imports a validated archive, merging it into or replacing the local profile.
+/// The local store is backed up first so the import can be undone.
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn import_profile_archive(app: AppHandle, file_path: String, mode: ImportMode) -> PreferenceResult<ArchivePreview> {
+    let path = scope::check_path(&app, &file_path)?;
+    let contents = read_archive(&path)?;
+
+    let local = local_preferences(&app)?;
+    let preview = preview_of(&local, &contents);
+    let local_user = local.get(PROFILE_USER_KEY).cloned();
+
+    let mut preferences = match mode {
+        ImportMode::Merge => merge_preferences(local, contents.preferences),
+        ImportMode::Replace => contents.preferences,
+    };
+
+    // A user taken from the archive must pass the same checks as one entered in the app
+    if preferences.get(PROFILE_USER_KEY) != local_user.as_ref() {
+        validate_imported_user(&app, &mut preferences)?;
+    }
+
+    storage::create_backup(&app)?;
+    save_all_preferences(app, preferences)?;
+
+    Ok(preview)
+}
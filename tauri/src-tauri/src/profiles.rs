@@ -0,0 +1,476 @@
+// profiles.rs - Several learner profiles on one machine, each with its own store
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use chrono::{DateTime, Utc};
+
+use crate::error::AppError;
+use crate::games::GameState;
+use crate::index::content_hash;
+use crate::search::SearchState;
+use crate::storage;
+use crate::text::normalize_input;
+use crate::{emit_to_frontend, load_profile_store, PreferenceResult, PROFILE_USER_KEY, STORE_FILE_NAME};
+
+/// Registry of profiles, next to the profile folders in the app data directory
+const REGISTRY_FILE_NAME: &str = "profiles.json";
+
+/// Folder holding one subfolder per profile with its store and backups
+const PROFILES_DIR_NAME: &str = "profiles";
+
+/// Folder of store backups, moved along with a pre-profile store
+const LEGACY_BACKUP_DIR_NAME: &str = "backups";
+
+/// Name of the profile created on first launch
+const DEFAULT_PROFILE_NAME: &str = "Default";
+
+const MAX_NAME_LENGTH: usize = 64;
+const MIN_PIN_LENGTH: usize = 4;
+const MAX_PIN_LENGTH: usize = 12;
+
+/// Active profile of this session. `None` until a profile is chosen.
+#[derive(Default)]
+pub struct ProfileState {
+    active: Mutex<Option<String>>,
+    /// Profiles opened so far in this session; launches are counted once per profile
+    opened: Mutex<HashSet<String>>,
+    /// Serializes read-modify-write cycles of the registry
+    registry: Mutex<()>,
+}
+
+/// Salted SHA-256 of a PIN. PINs keep learners on a shared machine out of
+/// each other's profiles; they are not meant to resist a determined attacker.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PinHash {
+    salt: String,
+    hash: String,
+}
+
+impl PinHash {
+    fn new(pin: &str) -> Self {
+        let salt = format!("{:032x}", rand::random::<u128>());
+        let hash = content_hash(format!("{}:{}", salt, pin).as_bytes());
+
+        PinHash { salt, hash }
+    }
+
+    fn matches(&self, pin: &str) -> bool {
+        content_hash(format!("{}:{}", self.salt, pin).as_bytes()) == self.hash
+    }
+}
+
+/// A profile as recorded in the registry
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ProfileEntry {
+    id: String,
+    name: String,
+    created_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+    pin: Option<PinHash>,
+}
+
+/// All profiles on this machine
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ProfileRegistry {
+    profiles: Vec<ProfileEntry>,
+}
+
+impl ProfileRegistry {
+    fn find(&self, profile_id: &str) -> PreferenceResult<&ProfileEntry> {
+        self.profiles.iter()
+            .find(|profile| profile.id == profile_id)
+            .ok_or_else(|| AppError::not_found(format!("Profile '{}' does not exist", profile_id)))
+    }
+
+    fn find_mut(&mut self, profile_id: &str) -> PreferenceResult<&mut ProfileEntry> {
+        self.profiles.iter_mut()
+            .find(|profile| profile.id == profile_id)
+            .ok_or_else(|| AppError::not_found(format!("Profile '{}' does not exist", profile_id)))
+    }
+}
+
+/// A profile as shown to the frontend
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub has_pin: bool,
+    pub active: bool,
+}
+
+impl ProfileInfo {
+    fn new(profile: &ProfileEntry, active_id: Option<&str>) -> Self {
+        ProfileInfo {
+            id: profile.id.clone(),
+            name: profile.name.clone(),
+            created_at: profile.created_at,
+            last_used_at: profile.last_used_at,
+            has_pin: profile.pin.is_some(),
+            active: active_id == Some(profile.id.as_str()),
+        }
+    }
+}
+
+/// App data directory
+fn data_dir(app: &AppHandle) -> PreferenceResult<PathBuf> {
+    app.path().app_data_dir()
+        .map_err(|e| AppError::storage(format!("Failed to resolve app data directory: {}", e)))
+}
+
+/// Folder of a profile, relative to the app data directory
+fn profile_dir(profile_id: &str) -> PathBuf {
+    Path::new(PROFILES_DIR_NAME).join(profile_id)
+}
+
+fn load_registry(app: &AppHandle) -> PreferenceResult<ProfileRegistry> {
+    let path = data_dir(app)?.join(REGISTRY_FILE_NAME);
+
+    if !path.exists() {
+        return Ok(ProfileRegistry::default());
+    }
+
+    let bytes = fs::read(&path)
+        .map_err(|e| AppError::io(&path, format!("Failed to read profiles '{}': {}", path.display(), e)))?;
+
+    serde_json::from_slice(&bytes)
+        .map_err(|e| AppError::parse(&path, Some(e.line()), format!("Failed to parse profiles: {}", e)))
+}
+
+fn save_registry(app: &AppHandle, registry: &ProfileRegistry) -> PreferenceResult<()> {
+    let bytes = serde_json::to_vec_pretty(registry)
+        .map_err(|e| AppError::storage(format!("Failed to serialize profiles: {}", e)))?;
+
+    storage::write_atomic(&data_dir(app)?.join(REGISTRY_FILE_NAME), &bytes)
+}
+
+fn lock_registry(state: &ProfileState) -> PreferenceResult<std::sync::MutexGuard<'_, ()>> {
+    state.registry.lock()
+        .map_err(|_| AppError::internal("Profile registry lock was poisoned"))
+}
+
+fn active_id(state: &ProfileState) -> PreferenceResult<Option<String>> {
+    state.active.lock()
+        .map(|active| active.clone())
+        .map_err(|_| AppError::internal("Active profile lock was poisoned"))
+}
+
+fn set_active_id(state: &ProfileState, profile_id: Option<String>) -> PreferenceResult<()> {
+    let mut active = state.active.lock()
+        .map_err(|_| AppError::internal("Active profile lock was poisoned"))?;
+    *active = profile_id;
+
+    Ok(())
+}
+
+/// Marks the active profile as opened in this session. Returns whether this is its first open.
+pub(crate) fn mark_active_opened(app: &AppHandle) -> PreferenceResult<bool> {
+    let state = app.state::<ProfileState>();
+    let Some(profile_id) = active_id(&state)? else {
+        return Ok(false);
+    };
+
+    let mut opened = state.opened.lock()
+        .map_err(|_| AppError::internal("Opened profiles lock was poisoned"))?;
+
+    Ok(opened.insert(profile_id))
+}
+
+/// Store file of the active profile, relative to the app data directory
+pub(crate) fn active_store_file(app: &AppHandle) -> PreferenceResult<PathBuf> {
+    let profile_id = active_id(&app.state::<ProfileState>())?
+        .ok_or_else(|| AppError::storage("No profile is selected; choose a profile first"))?;
+
+    Ok(profile_dir(&profile_id).join(STORE_FILE_NAME))
+}
+
+fn validate_name(registry: &ProfileRegistry, name: &str) -> PreferenceResult<String> {
+    let name = normalize_input(name);
+
+    if name.is_empty() {
+        return Err(AppError::invalid("name", "Profile name cannot be empty"));
+    }
+
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(AppError::invalid("name", format!("Profile name cannot be longer than {} characters", MAX_NAME_LENGTH)));
+    }
+
+    let taken = registry.profiles.iter()
+        .any(|profile| normalize_input(&profile.name).to_lowercase() == name.to_lowercase());
+    if taken {
+        return Err(AppError::invalid("name", format!("A profile named '{}' already exists", name)));
+    }
+
+    Ok(name)
+}
+
+fn validate_pin(field: &str, pin: &str) -> PreferenceResult<()> {
+    let length = pin.chars().count();
+
+    if !pin.chars().all(|character| character.is_ascii_digit()) || !(MIN_PIN_LENGTH..=MAX_PIN_LENGTH).contains(&length) {
+        return Err(AppError::invalid(
+            field,
+            format!("PIN must be {} to {} digits", MIN_PIN_LENGTH, MAX_PIN_LENGTH),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks the PIN of a protected profile; unprotected profiles accept any input
+fn verify_pin(profile: &ProfileEntry, field: &str, pin: Option<&str>) -> PreferenceResult<()> {
+    let Some(pin_hash) = &profile.pin else {
+        return Ok(());
+    };
+
+    match pin {
+        Some(pin) if pin_hash.matches(pin) => Ok(()),
+        Some(_) => Err(AppError::invalid(field, format!("Incorrect PIN for profile '{}'", profile.name))),
+        None => Err(AppError::invalid(field, format!("Profile '{}' requires a PIN", profile.name))),
+    }
+}
+
+fn new_profile(name: String, pin: Option<&str>) -> ProfileEntry {
+    ProfileEntry {
+        id: format!("{:016x}", rand::random::<u64>()),
+        name,
+        created_at: Utc::now(),
+        last_used_at: None,
+        pin: pin.map(PinHash::new),
+    }
+}
+
+/// Moves the store used before profiles existed, with its backups, into a first profile
+/// named after the learner. Returns `None` on a fresh install.
+fn migrate_legacy_store(app: &AppHandle) -> PreferenceResult<Option<ProfileEntry>> {
+    let data_dir = data_dir(app)?;
+    let legacy_path = data_dir.join(STORE_FILE_NAME);
+
+    if !legacy_path.exists() {
+        return Ok(None);
+    }
+
+    // A corrupt legacy store is still moved; recovery then runs inside the profile
+    let name = fs::read(&legacy_path).ok()
+        .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+        .and_then(|store| store.get(PROFILE_USER_KEY)?.get("fullName")?.as_str().map(str::to_string))
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PROFILE_NAME.to_string());
+
+    let profile = new_profile(name.trim().to_string(), None);
+    let profile_path = data_dir.join(profile_dir(&profile.id));
+    fs::create_dir_all(&profile_path)
+        .map_err(|e| AppError::io(&profile_path, format!("Failed to create profile folder: {}", e)))?;
+
+    fs::rename(&legacy_path, profile_path.join(STORE_FILE_NAME))
+        .map_err(|e| AppError::io(&legacy_path, format!("Failed to move store into profile: {}", e)))?;
+
+    let legacy_backups = data_dir.join(LEGACY_BACKUP_DIR_NAME);
+    if legacy_backups.is_dir() {
+        fs::rename(&legacy_backups, profile_path.join(LEGACY_BACKUP_DIR_NAME))
+            .map_err(|e| AppError::io(&legacy_backups, format!("Failed to move backups into profile: {}", e)))?;
+    }
+
+    Ok(Some(profile))
+}
+
+/// Marks a profile as active and records its use
+fn activate(app: &AppHandle, registry: &mut ProfileRegistry, profile_id: &str) -> PreferenceResult<ProfileInfo> {
+    let profile = registry.find_mut(profile_id)?;
+    profile.last_used_at = Some(Utc::now());
+    let info = ProfileInfo::new(profile, Some(profile_id));
+
+    save_registry(app, registry)?;
+    set_active_id(&app.state::<ProfileState>(), Some(profile_id.to_string()))?;
+
+    Ok(info)
+}
+
+/// Usernames of every profile except the active one, read from their stores.
+/// Profiles whose store cannot be read or has no user are skipped.
+pub(crate) fn other_usernames(app: &AppHandle) -> PreferenceResult<Vec<String>> {
+    let active = active_id(&app.state::<ProfileState>())?;
+    let data_dir = data_dir(app)?;
+    let registry = load_registry(app)?;
+
+    Ok(registry.profiles.iter()
+        .filter(|profile| active.as_deref() != Some(profile.id.as_str()))
+        .filter_map(|profile| {
+            let bytes = fs::read(data_dir.join(profile_dir(&profile.id)).join(STORE_FILE_NAME)).ok()?;
+            let store: Value = serde_json::from_slice(&bytes).ok()?;
+
+            store.get(PROFILE_USER_KEY)?.get("username")?.as_str().map(str::to_string)
+        })
+        .collect())
+}
+
+/// Loads the profile registry at startup, creating the first profile if needed, and
+/// reopens the most recently used profile without a PIN. Returns whether a profile
+/// was opened; when every profile is protected, `get_active_profile` returns null
+/// until the learner chose one with `switch_profile`.
+pub(crate) fn initialize_profiles(app: &AppHandle) -> PreferenceResult<bool> {
+    let state = app.state::<ProfileState>();
+    let _guard = lock_registry(&state)?;
+    let mut registry = load_registry(app)?;
+
+    if registry.profiles.is_empty() {
+        let profile = match migrate_legacy_store(app)? {
+            Some(profile) => profile,
+            None => new_profile(DEFAULT_PROFILE_NAME.to_string(), None),
+        };
+
+        registry.profiles.push(profile);
+        save_registry(app, &registry)?;
+    }
+
+    let last_unprotected = registry.profiles.iter()
+        .filter(|profile| profile.pin.is_none())
+        .max_by_key(|profile| profile.last_used_at)
+        .map(|profile| profile.id.clone());
+
+    match last_unprotected {
+        Some(profile_id) => {
+            activate(app, &mut registry, &profile_id)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Lists the profiles on this machine
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_profiles(app: AppHandle) -> PreferenceResult<Vec<ProfileInfo>> {
+    let state = app.state::<ProfileState>();
+    let active = active_id(&state)?;
+    let registry = load_registry(&app)?;
+
+    Ok(registry.profiles.iter()
+        .map(|profile| ProfileInfo::new(profile, active.as_deref()))
+        .collect())
+}
+
+/// Returns the active profile, or null while the learner still has to choose one
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_active_profile(app: AppHandle) -> PreferenceResult<Option<ProfileInfo>> {
+    let state = app.state::<ProfileState>();
+    let Some(active) = active_id(&state)? else {
+        return Ok(None);
+    };
+
+    let registry = load_registry(&app)?;
+    let profile = registry.find(&active)?;
+
+    Ok(Some(ProfileInfo::new(profile, Some(&active))))
+}
+
+/// Creates an empty profile, optionally protected by a PIN. The active profile does not change.
+#[tauri::command(rename_all = "camelCase")]
+pub fn create_profile(app: AppHandle, name: String, pin: Option<String>) -> PreferenceResult<ProfileInfo> {
+    let state = app.state::<ProfileState>();
+    let _guard = lock_registry(&state)?;
+    let mut registry = load_registry(&app)?;
+
+    let name = validate_name(&registry, &name)?;
+    if let Some(pin) = &pin {
+        validate_pin("pin", pin)?;
+    }
+
+    let profile = new_profile(name, pin.as_deref());
+    let info = ProfileInfo::new(&profile, None);
+
+    registry.profiles.push(profile);
+    save_registry(&app, &registry)?;
+
+    Ok(info)
+}
+
+/// Opens another profile and loads its store
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn switch_profile(app: AppHandle, profile_id: String, pin: Option<String>) -> PreferenceResult<ProfileInfo> {
+    let info = {
+        let state = app.state::<ProfileState>();
+        let _guard = lock_registry(&state)?;
+        let mut registry = load_registry(&app)?;
+
+        verify_pin(registry.find(&profile_id)?, "pin", pin.as_deref())?;
+        activate(&app, &mut registry, &profile_id)?
+    };
+
+    // Search indexes and game rounds were built for the previous profile
+    app.state::<SearchState>().clear()?;
+    app.state::<GameState>().clear()?;
+
+    load_profile_store(&app)?;
+
+    let payload = serde_json::to_value(&info)
+        .map_err(|e| AppError::internal(format!("Failed to serialize profile: {}", e)))?;
+    emit_to_frontend(&app, "profile-switched", payload)?;
+
+    Ok(info)
+}
+
+/// Sets, changes or removes (with `newPin` omitted) the PIN of a profile
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_profile_pin(
+    app: AppHandle,
+    profile_id: String,
+    current_pin: Option<String>,
+    new_pin: Option<String>,
+) -> PreferenceResult<ProfileInfo> {
+    let state = app.state::<ProfileState>();
+    let _guard = lock_registry(&state)?;
+    let active = active_id(&state)?;
+    let mut registry = load_registry(&app)?;
+
+    if let Some(new_pin) = &new_pin {
+        validate_pin("newPin", new_pin)?;
+    }
+
+    let profile = registry.find_mut(&profile_id)?;
+    verify_pin(profile, "currentPin", current_pin.as_deref())?;
+    profile.pin = new_pin.as_deref().map(PinHash::new);
+    let info = ProfileInfo::new(profile, active.as_deref());
+
+    save_registry(&app, &registry)?;
+
+    Ok(info)
+}
+
+/// Deletes a profile with its store, history and backups. The active profile
+/// and the last remaining profile cannot be deleted.
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn delete_profile(app: AppHandle, profile_id: String, pin: Option<String>) -> PreferenceResult<()> {
+    let state = app.state::<ProfileState>();
+    let _guard = lock_registry(&state)?;
+    let mut registry = load_registry(&app)?;
+
+    verify_pin(registry.find(&profile_id)?, "pin", pin.as_deref())?;
+
+    if active_id(&state)?.as_deref() == Some(profile_id.as_str()) {
+        return Err(AppError::invalid("profileId", "The active profile cannot be deleted; switch to another profile first"));
+    }
+
+    if registry.profiles.len() == 1 {
+        return Err(AppError::invalid("profileId", "The last profile cannot be deleted"));
+    }
+
+    registry.profiles.retain(|profile| profile.id != profile_id);
+    save_registry(&app, &registry)?;
+
+    let profile_path = data_dir(&app)?.join(profile_dir(&profile_id));
+    if profile_path.exists() {
+        fs::remove_dir_all(&profile_path)
+            .map_err(|e| AppError::io(&profile_path, format!("Failed to delete profile data: {}", e)))?;
+    }
+
+    Ok(())
+}
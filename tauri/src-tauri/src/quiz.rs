@@ -0,0 +1,184 @@
+// quiz.rs - Multiple-choice quizzes with distractors drawn from the deck
+use std::collections::HashSet;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::index::{self, CardRecord};
+use crate::text::{fold, normalize_headword, normalize_translation};
+use crate::PreferenceResult;
+
+/// Choices per question, including the correct one
+const CHOICES_PER_QUESTION: usize = 4;
+
+/// Answers whose length differs by at most this many characters look alike
+const SIMILAR_LENGTH_CHARS: usize = 3;
+
+/// Which side of the card is asked
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum QuizDirection {
+    ItalianToEnglish,
+    EnglishToItalian,
+    /// Picks a direction at random for every question
+    Mixed,
+}
+
+/// A multiple-choice question
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizQuestion {
+    pub card_id: String,
+    pub direction: QuizDirection,
+    pub prompt: String,
+    pub choices: Vec<String>,
+    pub correct_index: usize,
+}
+
+/// Answers accepted for a prompt, normalized for comparison
+struct ValidAnswers {
+    headwords: HashSet<String>,
+    translations: HashSet<String>,
+}
+
+impl ValidAnswers {
+    fn of(card: &CardRecord) -> Self {
+        Self {
+            headwords: card.italian_forms.iter().map(|form| normalize_headword(form)).collect(),
+            translations: card.english.iter().map(|translation| normalize_translation(translation)).collect(),
+        }
+    }
+
+    /// Whether a card would also answer the prompt: it shares a headword, or
+    /// (in the given direction) one of its translations is a valid answer
+    fn accepts(&self, candidate: &CardRecord, direction: QuizDirection, prompt: &str) -> bool {
+        let shares_headword = candidate.italian_forms.iter()
+            .any(|form| self.headwords.contains(&normalize_headword(form)));
+
+        let shares_translation = match direction {
+            QuizDirection::EnglishToItalian => candidate.english.iter()
+                .any(|translation| normalize_translation(translation) == normalize_translation(prompt)),
+            _ => candidate.english.iter()
+                .any(|translation| self.translations.contains(&normalize_translation(translation))),
+        };
+
+        shares_headword || shares_translation
+    }
+}
+
+/// How plausible a card is as a distractor for another: same part of
+/// speech, shared tags and similar answer length all make it harder to rule out
+fn distractor_score(card: &CardRecord, candidate: &CardRecord, answer: &str, candidate_answer: &str) -> u32 {
+    let same_part_of_speech = match (&card.part_of_speech, &candidate.part_of_speech) {
+        (Some(part), Some(candidate_part)) => fold(part) == fold(candidate_part),
+        _ => false,
+    };
+
+    let shared_tags = candidate.tags.iter()
+        .filter(|tag| card.tags.iter().any(|card_tag| fold(card_tag) == fold(tag)))
+        .count() as u32;
+
+    let similar_length = answer.chars().count().abs_diff(candidate_answer.chars().count()) <= SIMILAR_LENGTH_CHARS;
+
+    u32::from(same_part_of_speech) * 4 + shared_tags.min(2) * 2 + u32::from(similar_length)
+}
+
+/// Text shown for a card as an answer in the given direction
+fn answer_text(card: &CardRecord, direction: QuizDirection) -> Option<&str> {
+    match direction {
+        QuizDirection::EnglishToItalian => Some(card.italian.as_str()),
+        _ => card.english.first().map(String::as_str),
+    }
+}
+
+/// Direction of a single question, choosing one at random for mixed quizzes
+pub(crate) fn question_direction<R: Rng>(direction: QuizDirection, rng: &mut R) -> QuizDirection {
+    match direction {
+        QuizDirection::Mixed if rng.gen_bool(0.5) => QuizDirection::ItalianToEnglish,
+        QuizDirection::Mixed => QuizDirection::EnglishToItalian,
+        direction => direction,
+    }
+}
+
+/// Builds one question, or `None` when the deck has no usable distractor
+pub(crate) fn build_question<R: Rng>(
+    card: &CardRecord,
+    cards: &[CardRecord],
+    direction: QuizDirection,
+    rng: &mut R,
+) -> Option<QuizQuestion> {
+    let (prompt, answer) = match direction {
+        QuizDirection::EnglishToItalian => (card.english.choose(rng)?.clone(), card.italian.clone()),
+        _ => (card.italian.clone(), card.english.first()?.clone()),
+    };
+
+    let valid_answers = ValidAnswers::of(card);
+
+    let mut candidates: Vec<(u32, u32, &str)> = cards.iter()
+        .filter(|candidate| candidate.id != card.id)
+        .filter(|candidate| !valid_answers.accepts(candidate, direction, &prompt))
+        .filter_map(|candidate| {
+            let candidate_answer = answer_text(candidate, direction)?;
+            let score = distractor_score(card, candidate, &answer, candidate_answer);
+            // Random tie-breaker so equally plausible distractors vary between quizzes
+            Some((score, rng.gen::<u32>(), candidate_answer))
+        })
+        .collect();
+
+    candidates.sort_by(|candidate_a, candidate_b| candidate_b.0.cmp(&candidate_a.0).then(candidate_a.1.cmp(&candidate_b.1)));
+
+    let mut seen = HashSet::from([fold(&answer)]);
+    let mut choices: Vec<String> = candidates.into_iter()
+        .map(|(_, _, candidate_answer)| candidate_answer.to_string())
+        .filter(|candidate_answer| seen.insert(fold(candidate_answer)))
+        .take(CHOICES_PER_QUESTION - 1)
+        .collect();
+
+    if choices.is_empty() {
+        return None;
+    }
+
+    let correct_index = rng.gen_range(0..=choices.len());
+    choices.insert(correct_index, answer);
+
+    Some(QuizQuestion {
+        card_id: card.id.clone(),
+        direction,
+        prompt,
+        choices,
+        correct_index,
+    })
+}
+
+/// Generates up to `size` multiple-choice questions from random cards of a deck.
+/// Distractors come from the same deck, preferring the same part of speech,
+/// shared tags and similar length, and never include another valid answer.
+#[tauri::command(rename_all = "camelCase")]
+pub fn generate_quiz(
+    app: AppHandle,
+    directory_path: String,
+    size: usize,
+    direction: QuizDirection,
+) -> PreferenceResult<Vec<QuizQuestion>> {
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+
+    if snapshot.cards.len() < 2 {
+        return Err(AppError::invalid("directoryPath", "A quiz needs at least two cards in the deck"));
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut order: Vec<&CardRecord> = snapshot.cards.iter().collect();
+    order.shuffle(&mut rng);
+
+    let questions = order.into_iter()
+        .filter_map(|card| {
+            let direction = question_direction(direction, &mut rng);
+            build_question(card, &snapshot.cards, direction, &mut rng)
+        })
+        .take(size)
+        .collect();
+
+    Ok(questions)
+}
@@ -0,0 +1,789 @@
+// repository.rs - Git support for deck folders
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, TimeZone, Utc};
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{
+    CheckoutNotificationType, Commit, ErrorCode, FetchOptions, Oid, RemoteCallbacks, Repository,
+    Signature, Status, StatusOptions,
+};
+use serde::Serialize;
+use serde_json::json;
+use tauri::AppHandle;
+
+use crate::authoring;
+use crate::decks;
+use crate::error::AppError;
+use crate::index::{self, card_id, DeckSnapshot};
+use crate::scope;
+use crate::{emit_to_frontend, get_store, PreferenceResult, ProfileUser, PROFILE_USER_KEY};
+
+/// Default number of commits returned by `get_card_history`
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Event emitted while cloning or pulling
+const PROGRESS_EVENT: &str = "repository-progress";
+
+/// Summary of a repository that contains a deck
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryInfo {
+    workdir: String,
+    deck_path: String,
+    branch: Option<String>,
+    head: Option<CommitInfo>,
+    changed_files: usize,
+}
+
+/// Result of cloning a deck repository
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneResult {
+    repository: RepositoryInfo,
+    snapshot: DeckSnapshot,
+}
+
+/// Result of pulling a deck repository
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullResult {
+    /// One of "upToDate", "fastForward", "merged" or "conflicts"
+    outcome: &'static str,
+    /// Files that prevented the pull, relative to the deck when inside it
+    conflicts: Vec<String>,
+    head: Option<CommitInfo>,
+    snapshot: Option<DeckSnapshot>,
+}
+
+/// A single commit as shown in the history view
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitInfo {
+    id: String,
+    short_id: String,
+    summary: String,
+    message: String,
+    author_name: String,
+    author_email: String,
+    time: DateTime<Utc>,
+}
+
+/// Working tree state of a single note file
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteFileStatus {
+    /// Path relative to the deck root, matching card ids
+    id: String,
+    path: String,
+    status: &'static str,
+    staged: bool,
+}
+
+impl CommitInfo {
+    /// Creates commit information from a git commit
+    fn from_commit(commit: &Commit) -> Self {
+        let id = commit.id().to_string();
+        let author = commit.author();
+
+        Self {
+            short_id: id.chars().take(7).collect(),
+            id,
+            summary: commit.summary().unwrap_or_default().to_string(),
+            message: commit.message().unwrap_or_default().to_string(),
+            author_name: author.name().unwrap_or_default().to_string(),
+            author_email: author.email().unwrap_or_default().to_string(),
+            time: Utc.timestamp_opt(commit.time().seconds(), 0)
+                .single()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Repository containing a deck together with the deck location inside it
+pub(crate) struct DeckRepository {
+    pub(crate) repository: Repository,
+    pub(crate) workdir: PathBuf,
+    pub(crate) deck_root: PathBuf,
+}
+
+impl DeckRepository {
+    /// Path of a deck file relative to the repository working directory
+    fn repository_path(&self, deck_file: &Path) -> PreferenceResult<PathBuf> {
+        deck_file.strip_prefix(&self.workdir)
+            .map(Path::to_path_buf)
+            .map_err(|_| AppError::OutOfScope {
+                path: deck_file.to_string_lossy().to_string(),
+                message: format!("File '{}' is outside the repository", deck_file.display()),
+            })
+    }
+
+    /// Relative path (with '/' separators) of the deck root inside the repository
+    fn deck_pathspec(&self) -> String {
+        card_id(&self.workdir, &self.deck_root)
+    }
+}
+
+/// Opens the git repository that contains the given deck directory
+pub(crate) fn open_deck_repository_at(app: &AppHandle, directory_path: &str) -> PreferenceResult<DeckRepository> {
+    let deck_root = scope::scoped_deck_path(app, directory_path)?;
+
+    let repository = Repository::discover(&deck_root)
+        .map_err(|e| AppError::repository(format!("Directory '{}' is not inside a git repository: {}", directory_path, e.message())))?;
+
+    let workdir = repository.workdir()
+        .ok_or_else(|| AppError::repository("Bare repositories cannot be used as decks"))?
+        .canonicalize()
+        .map_err(|e| AppError::repository(format!("Failed to resolve repository working directory: {}", e)))?;
+
+    Ok(DeckRepository {
+        repository,
+        workdir,
+        deck_root,
+    })
+}
+
+/// Returns true for files the deck index would read
+fn is_note_file(path: &str) -> bool {
+    path.ends_with(".md")
+        && !path.split('/').any(|component| component.starts_with('.'))
+}
+
+/// Maps git status flags to a single label and whether the change is staged
+fn describe_status(status: Status) -> Option<(&'static str, bool)> {
+    let described = if status.is_conflicted() {
+        ("conflicted", false)
+    } else if status.is_wt_new() {
+        ("untracked", false)
+    } else if status.is_wt_modified() {
+        ("modified", false)
+    } else if status.is_wt_deleted() {
+        ("deleted", false)
+    } else if status.is_wt_renamed() {
+        ("renamed", false)
+    } else if status.is_wt_typechange() {
+        ("typechange", false)
+    } else if status.is_index_new() {
+        ("added", true)
+    } else if status.is_index_modified() {
+        ("modified", true)
+    } else if status.is_index_deleted() {
+        ("deleted", true)
+    } else if status.is_index_renamed() {
+        ("renamed", true)
+    } else if status.is_index_typechange() {
+        ("typechange", true)
+    } else {
+        return None;
+    };
+
+    Some(described)
+}
+
+/// Collects changed and untracked note files inside the deck
+pub(crate) fn note_file_statuses(deck_repository: &DeckRepository) -> PreferenceResult<Vec<NoteFileStatus>> {
+    let mut options = StatusOptions::new();
+    options.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+
+    let pathspec = deck_repository.deck_pathspec();
+    if !pathspec.is_empty() {
+        options.pathspec(&pathspec);
+    }
+
+    let statuses = deck_repository.repository.statuses(Some(&mut options))
+        .map_err(|e| AppError::repository(format!("Failed to read repository status: {}", e.message())))?;
+
+    let mut files: Vec<NoteFileStatus> = statuses.iter()
+        .filter_map(|entry| {
+            let relative_path = entry.path()?.to_string();
+            if !is_note_file(&relative_path) {
+                return None;
+            }
+
+            let (status, staged) = describe_status(entry.status())?;
+            let full_path = deck_repository.workdir.join(&relative_path);
+
+            Some(NoteFileStatus {
+                id: card_id(&deck_repository.deck_root, &full_path),
+                path: full_path.to_string_lossy().to_string(),
+                status,
+                staged,
+            })
+        })
+        .collect();
+
+    files.sort_by(|file_a, file_b| file_a.id.cmp(&file_b.id));
+
+    Ok(files)
+}
+
+/// Commit signature from git config, falling back to the local profile
+fn commit_signature(app: &AppHandle, repository: &Repository) -> PreferenceResult<Signature<'static>> {
+    if let Ok(signature) = repository.signature() {
+        return Ok(signature.to_owned());
+    }
+
+    let store = get_store(app)?;
+    let profile_user = store.get(PROFILE_USER_KEY)
+        .and_then(|value| serde_json::from_value::<ProfileUser>(value).ok())
+        .ok_or_else(|| AppError::repository("No git identity configured and no profile to commit as"))?;
+
+    Signature::now(&profile_user.full_name, &profile_user.email)
+        .map_err(|e| AppError::repository(format!("Failed to create commit signature: {}", e.message())))
+}
+
+/// Returns the current HEAD commit, or None on a branch without commits yet
+fn head_commit(repository: &Repository) -> PreferenceResult<Option<Commit<'_>>> {
+    match repository.head() {
+        Ok(head) => head.peel_to_commit()
+            .map(Some)
+            .map_err(|e| AppError::repository(format!("Failed to resolve HEAD commit: {}", e.message()))),
+        Err(e) if e.code() == ErrorCode::UnbornBranch || e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(AppError::repository(format!("Failed to read repository HEAD: {}", e.message()))),
+    }
+}
+
+/// Builds the summary shown for an opened deck repository
+fn repository_info(deck_repository: &DeckRepository) -> PreferenceResult<RepositoryInfo> {
+    let changed_files = note_file_statuses(deck_repository)?.len();
+
+    let repository = &deck_repository.repository;
+    let branch = repository.head()
+        .ok()
+        .and_then(|head| head.shorthand().map(str::to_string));
+    let head = head_commit(repository)?.map(|commit| CommitInfo::from_commit(&commit));
+
+    Ok(RepositoryInfo {
+        workdir: deck_repository.workdir.to_string_lossy().to_string(),
+        deck_path: deck_repository.deck_root.to_string_lossy().to_string(),
+        branch,
+        head,
+        changed_files,
+    })
+}
+
+/// Remote callbacks that forward transfer progress to the frontend, once per percent
+fn progress_callbacks<'a>(app: &'a AppHandle, operation: &'static str) -> RemoteCallbacks<'a> {
+    let last_percent = Cell::new(None);
+    let mut callbacks = RemoteCallbacks::new();
+
+    callbacks.transfer_progress(move |progress| {
+        let (stage, done, total) = match progress.received_objects() < progress.total_objects() {
+            true => ("receiving", progress.received_objects(), progress.total_objects()),
+            false => ("resolving", progress.indexed_deltas(), progress.total_deltas()),
+        };
+        let percent = (done * 100).checked_div(total).unwrap_or(100);
+
+        if last_percent.replace(Some((stage, percent))) != Some((stage, percent)) {
+            let payload = json!({
+                "operation": operation,
+                "stage": stage,
+                "current": done,
+                "total": total,
+                "percent": percent,
+                "receivedBytes": progress.received_bytes()
+            });
+            // Progress is best effort, a failed emit must not abort the transfer
+            let _ = emit_to_frontend(app, PROGRESS_EVENT, payload);
+        }
+
+        true
+    });
+
+    callbacks
+}
+
+/// Name used for a conflicting repository path: the card id when inside the deck
+fn conflict_name(deck_repository: &DeckRepository, relative_path: &Path) -> String {
+    let full_path = deck_repository.workdir.join(relative_path);
+
+    match full_path.starts_with(&deck_repository.deck_root) {
+        true => card_id(&deck_repository.deck_root, &full_path),
+        false => card_id(&deck_repository.workdir, &full_path),
+    }
+}
+
+/// Checks out a tree without overwriting local changes.
+/// Returns the paths that blocked the checkout, empty on success.
+fn safe_checkout(deck_repository: &DeckRepository, commit_id: Oid) -> PreferenceResult<Vec<String>> {
+    let repository = &deck_repository.repository;
+    let commit = repository.find_commit(commit_id)
+        .map_err(|e| AppError::repository(format!("Failed to read commit: {}", e.message())))?;
+
+    let blocked_paths = RefCell::new(Vec::new());
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe()
+        .notify_on(CheckoutNotificationType::CONFLICT)
+        .notify(|_, path, _, _, _| {
+            if let Some(path) = path {
+                blocked_paths.borrow_mut().push(conflict_name(deck_repository, path));
+            }
+            true
+        });
+
+    let result = repository.checkout_tree(commit.as_object(), Some(&mut checkout));
+    drop(checkout);
+    let blocked_paths = blocked_paths.into_inner();
+
+    match result {
+        Ok(()) => Ok(Vec::new()),
+        Err(e) if e.code() == ErrorCode::Conflict && !blocked_paths.is_empty() => Ok(blocked_paths),
+        Err(e) => Err(AppError::repository(format!("Failed to check out pulled changes: {}", e.message()))),
+    }
+}
+
+/// Opens a deck folder as a git repository
+#[tauri::command(rename_all = "camelCase")]
+pub fn open_deck_repository(app: AppHandle, directory_path: String) -> PreferenceResult<RepositoryInfo> {
+    let deck_repository = open_deck_repository_at(&app, &directory_path)?;
+
+    repository_info(&deck_repository)
+}
+
+/// Clones a deck repository (any URL git understands, including `file://` and
+/// local bare repositories) and indexes it as a deck
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn clone_deck_repository(app: AppHandle, url: String, target_dir: String) -> PreferenceResult<CloneResult> {
+    if url.trim().is_empty() {
+        return Err(AppError::invalid("url", "Repository URL cannot be empty"));
+    }
+
+    if target_dir.trim().is_empty() {
+        return Err(AppError::invalid("targetDir", "Target directory cannot be empty"));
+    }
+
+    // Only clone into a new or empty directory inside a granted folder
+    let target_path = scope::check_path(&app, &target_dir)?;
+    if target_path.exists() {
+        let is_empty = fs::read_dir(&target_path)
+            .map_err(|e| AppError::io(&target_path, format!("Failed to read directory '{}': {}", target_dir, e)))?
+            .next()
+            .is_none();
+
+        if !is_empty {
+            return Err(AppError::already_exists(&target_path, format!("Target directory '{}' is not empty", target_dir)));
+        }
+    }
+
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(progress_callbacks(&app, "clone"));
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.progress(|_, current, total| {
+        let payload = json!({
+            "operation": "clone",
+            "stage": "checkout",
+            "current": current,
+            "total": total,
+            "percent": (current * 100).checked_div(total).unwrap_or(100)
+        });
+        let _ = emit_to_frontend(&app, PROGRESS_EVENT, payload);
+    });
+
+    clone_repository(url.trim(), &target_path, fetch_options, checkout)?;
+
+    let deck_repository = open_deck_repository_at(&app, &target_dir)?;
+    let repository = repository_info(&deck_repository)?;
+
+    let snapshot = index::load_deck(&app, &target_dir, true)?;
+    decks::record_deck_opened(&app, &snapshot)?;
+
+    Ok(CloneResult { repository, snapshot })
+}
+
+/// Clones a repository into a new or empty directory
+fn clone_repository(
+    url: &str,
+    target_path: &Path,
+    fetch_options: FetchOptions<'_>,
+    checkout: CheckoutBuilder<'_>,
+) -> PreferenceResult<Repository> {
+    RepoBuilder::new()
+        .fetch_options(fetch_options)
+        .with_checkout(checkout)
+        .clone(url, target_path)
+        .map_err(|e| AppError::repository(format!("Failed to clone '{}': {}", url, e.message())))
+}
+
+/// Fetches the upstream branch of a repository and fast-forwards or merges it.
+/// Returns the outcome with the paths that prevented it, which leave the
+/// working tree and branch untouched. `signature` is only asked for when a
+/// merge commit has to be created.
+fn merge_upstream<F>(
+    deck_repository: &DeckRepository,
+    fetch_options: &mut FetchOptions<'_>,
+    signature: F,
+) -> PreferenceResult<(&'static str, Vec<String>)>
+where
+    F: FnOnce() -> PreferenceResult<Signature<'static>>,
+{
+    let repository = &deck_repository.repository;
+
+    let head = repository.head()
+        .map_err(|e| AppError::repository(format!("Failed to read repository HEAD: {}", e.message())))?;
+    let branch_ref = head.name()
+        .filter(|_| head.is_branch())
+        .ok_or_else(|| AppError::repository("Cannot pull while HEAD is detached"))?
+        .to_string();
+
+    // Remote and upstream ref of the current branch, defaulting to origin/<branch>
+    let remote_name = repository.branch_upstream_remote(&branch_ref)
+        .ok()
+        .and_then(|name| name.as_str().map(str::to_string))
+        .unwrap_or_else(|| "origin".to_string());
+    let upstream_ref = repository.branch_upstream_name(&branch_ref)
+        .ok()
+        .and_then(|name| name.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("refs/remotes/{}/{}", remote_name, head.shorthand().unwrap_or_default()));
+
+    let mut remote = repository.find_remote(&remote_name)
+        .map_err(|e| AppError::repository(format!("Remote '{}' not found: {}", remote_name, e.message())))?;
+
+    remote.fetch(&[] as &[&str], Some(fetch_options), None)
+        .map_err(|e| AppError::repository(format!("Failed to fetch from '{}': {}", remote_name, e.message())))?;
+
+    let upstream = repository.find_reference(&upstream_ref)
+        .map_err(|e| AppError::repository(format!("Upstream branch '{}' not found: {}", upstream_ref, e.message())))?;
+    let their_commit = upstream.peel_to_commit()
+        .map_err(|e| AppError::repository(format!("Failed to resolve upstream commit: {}", e.message())))?;
+    let their_annotated = repository.reference_to_annotated_commit(&upstream)
+        .map_err(|e| AppError::repository(format!("Failed to resolve upstream commit: {}", e.message())))?;
+
+    let (analysis, _) = repository.merge_analysis(&[&their_annotated])
+        .map_err(|e| AppError::repository(format!("Failed to analyse merge: {}", e.message())))?;
+
+    if analysis.is_up_to_date() {
+        return Ok(("upToDate", Vec::new()));
+    }
+
+    if analysis.is_fast_forward() || analysis.is_unborn() {
+        let blocked_paths = safe_checkout(deck_repository, their_commit.id())?;
+        if !blocked_paths.is_empty() {
+            return Ok(("conflicts", blocked_paths));
+        }
+
+        repository.reference(&branch_ref, their_commit.id(), true, "pull: fast-forward")
+            .map_err(|e| AppError::repository(format!("Failed to update branch: {}", e.message())))?;
+
+        return Ok(("fastForward", Vec::new()));
+    }
+
+    let our_commit = head.peel_to_commit()
+        .map_err(|e| AppError::repository(format!("Failed to resolve HEAD commit: {}", e.message())))?;
+
+    let mut merged_index = repository.merge_commits(&our_commit, &their_commit, None)
+        .map_err(|e| AppError::repository(format!("Failed to merge upstream changes: {}", e.message())))?;
+
+    if merged_index.has_conflicts() {
+        let conflicts = merged_index.conflicts()
+            .map_err(|e| AppError::repository(format!("Failed to read merge conflicts: {}", e.message())))?
+            .filter_map(Result::ok)
+            .filter_map(|conflict| conflict.our.or(conflict.their))
+            .map(|entry| conflict_name(deck_repository, Path::new(&*String::from_utf8_lossy(&entry.path))))
+            .collect();
+
+        return Ok(("conflicts", conflicts));
+    }
+
+    let tree_id = merged_index.write_tree_to(repository)
+        .map_err(|e| AppError::repository(format!("Failed to write merged tree: {}", e.message())))?;
+    let tree = repository.find_tree(tree_id)
+        .map_err(|e| AppError::repository(format!("Failed to find merged tree: {}", e.message())))?;
+
+    let signature = signature()?;
+    let message = format!("Merge {} into {}", upstream_ref, head.shorthand().unwrap_or_default());
+    let merge_commit_id = repository.commit(None, &signature, &signature, &message, &tree, &[&our_commit, &their_commit])
+        .map_err(|e| AppError::repository(format!("Failed to create merge commit: {}", e.message())))?;
+
+    let blocked_paths = safe_checkout(deck_repository, merge_commit_id)?;
+    if !blocked_paths.is_empty() {
+        return Ok(("conflicts", blocked_paths));
+    }
+
+    repository.reference(&branch_ref, merge_commit_id, true, "pull: merge")
+        .map_err(|e| AppError::repository(format!("Failed to update branch: {}", e.message())))?;
+
+    Ok(("merged", Vec::new()))
+}
+
+/// Fetches and merges the upstream branch of a deck repository.
+/// Conflicts are reported without touching the working tree.
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn pull_deck_repository(app: AppHandle, directory_path: String) -> PreferenceResult<PullResult> {
+    let deck_repository = open_deck_repository_at(&app, &directory_path)?;
+
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(progress_callbacks(&app, "pull"));
+
+    let (outcome, conflicts) = merge_upstream(&deck_repository, &mut fetch_options, || {
+        commit_signature(&app, &deck_repository.repository)
+    })?;
+
+    if !conflicts.is_empty() {
+        return Ok(PullResult { outcome, conflicts, head: None, snapshot: None });
+    }
+
+    let head = head_commit(&deck_repository.repository)?.map(|commit| CommitInfo::from_commit(&commit));
+
+    // Re-index so changed notes are parsed again
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+
+    Ok(PullResult {
+        outcome,
+        conflicts,
+        head,
+        snapshot: Some(snapshot),
+    })
+}
+
+/// Lists changed and untracked note files of a deck
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_repository_status(app: AppHandle, directory_path: String) -> PreferenceResult<Vec<NoteFileStatus>> {
+    let deck_repository = open_deck_repository_at(&app, &directory_path)?;
+
+    note_file_statuses(&deck_repository)
+}
+
+/// Commits note changes of a deck. Without `card_ids`, every changed note is committed.
+#[tauri::command(rename_all = "camelCase")]
+pub fn commit_deck_changes(
+    app: AppHandle,
+    directory_path: String,
+    message: String,
+    card_ids: Option<Vec<String>>,
+) -> PreferenceResult<CommitInfo> {
+    if message.trim().is_empty() {
+        return Err(AppError::invalid("message", "Commit message cannot be empty"));
+    }
+
+    let deck_repository = open_deck_repository_at(&app, &directory_path)?;
+    let changed_files = note_file_statuses(&deck_repository)?;
+
+    let selected_files: Vec<&NoteFileStatus> = match &card_ids {
+        Some(ids) => ids.iter()
+            .map(|id| {
+                changed_files.iter()
+                    .find(|file| &file.id == id)
+                    .ok_or_else(|| AppError::invalid("cardIds", format!("Card '{}' has no changes to commit", id)))
+            })
+            .collect::<PreferenceResult<_>>()?,
+        None => changed_files.iter().collect(),
+    };
+
+    if selected_files.is_empty() {
+        return Err(AppError::invalid("cardIds", "There are no note changes to commit"));
+    }
+
+    let repository = &deck_repository.repository;
+    let mut repository_index = repository.index()
+        .map_err(|e| AppError::repository(format!("Failed to open repository index: {}", e.message())))?;
+
+    // Stage additions and modifications, and record deletions
+    for file in &selected_files {
+        let full_path = PathBuf::from(&file.path);
+        let relative_path = deck_repository.repository_path(&full_path)?;
+
+        let staged = match full_path.exists() {
+            true => repository_index.add_path(&relative_path),
+            false => repository_index.remove_path(&relative_path),
+        };
+        staged.map_err(|e| AppError::repository(format!("Failed to stage '{}': {}", file.id, e.message())))?;
+    }
+
+    repository_index.write()
+        .map_err(|e| AppError::repository(format!("Failed to write repository index: {}", e.message())))?;
+
+    let tree_id = repository_index.write_tree()
+        .map_err(|e| AppError::repository(format!("Failed to write tree: {}", e.message())))?;
+    let tree = repository.find_tree(tree_id)
+        .map_err(|e| AppError::repository(format!("Failed to find tree: {}", e.message())))?;
+
+    let signature = commit_signature(&app, repository)?;
+    let parent = head_commit(repository)?;
+    let parents: Vec<&Commit> = parent.iter().collect();
+
+    let commit_id = repository.commit(Some("HEAD"), &signature, &signature, message.trim(), &tree, &parents)
+        .map_err(|e| AppError::repository(format!("Failed to create commit: {}", e.message())))?;
+
+    let commit = repository.find_commit(commit_id)
+        .map_err(|e| AppError::repository(format!("Failed to read new commit: {}", e.message())))?;
+
+    Ok(CommitInfo::from_commit(&commit))
+}
+
+/// Lists the commits that changed a single card file, newest first
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_card_history(
+    app: AppHandle,
+    directory_path: String,
+    card_id: String,
+    limit: Option<usize>,
+) -> PreferenceResult<Vec<CommitInfo>> {
+    let deck_repository = open_deck_repository_at(&app, &directory_path)?;
+    let repository = &deck_repository.repository;
+
+    // Card ids come from the webview; deleted cards still resolve so their history can be listed
+    let card_path = authoring::resolve_in_deck(&deck_repository.deck_root, &card_id)?;
+    let relative_path = deck_repository.repository_path(&card_path)?;
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+
+    if head_commit(repository)?.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut revwalk = repository.revwalk()
+        .map_err(|e| AppError::repository(format!("Failed to walk history: {}", e.message())))?;
+    revwalk.push_head()
+        .map_err(|e| AppError::repository(format!("Failed to walk history: {}", e.message())))?;
+
+    // Blob id of the file in a commit, or None if the file is absent
+    let blob_in = |commit: &Commit| {
+        commit.tree()
+            .ok()
+            .and_then(|tree| tree.get_path(&relative_path).ok())
+            .map(|entry| entry.id())
+    };
+
+    let mut history = Vec::new();
+
+    for commit_id in revwalk {
+        let commit_id = commit_id
+            .map_err(|e| AppError::repository(format!("Failed to walk history: {}", e.message())))?;
+        let commit = repository.find_commit(commit_id)
+            .map_err(|e| AppError::repository(format!("Failed to read commit: {}", e.message())))?;
+
+        let current_blob = blob_in(&commit);
+        let parent_blob = commit.parent(0).ok().and_then(|parent| blob_in(&parent));
+
+        // The commit touched the file if its content differs from the first parent
+        if current_blob != parent_blob {
+            history.push(CommitInfo::from_commit(&commit));
+            if history.len() >= limit {
+                break;
+            }
+        }
+    }
+
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{IndexAddOption, RepositoryInitOptions};
+    use tempfile::TempDir;
+
+    const BRANCH: &str = "main";
+
+    fn signature() -> PreferenceResult<Signature<'static>> {
+        Signature::now("Tester", "tester@example.com")
+            .map_err(|e| AppError::repository(e.message().to_string()))
+    }
+
+    /// Writes a note and commits every change in the working tree
+    fn commit_note(repository: &Repository, name: &str, content: &str) {
+        let workdir = repository.workdir().unwrap();
+        fs::write(workdir.join(name), content).unwrap();
+
+        let mut index = repository.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+
+        let parent = head_commit(repository).unwrap();
+        let parents: Vec<&Commit> = parent.iter().collect();
+        let signature = signature().unwrap();
+        repository.commit(Some("HEAD"), &signature, &signature, name, &tree, &parents).unwrap();
+    }
+
+    fn push(repository: &Repository) {
+        let refspec = format!("refs/heads/{0}:refs/heads/{0}", BRANCH);
+        repository.find_remote("origin").unwrap().push(&[refspec.as_str()], None).unwrap();
+    }
+
+    fn clone(url: &Path, target: &Path) -> DeckRepository {
+        let repository = clone_repository(&url.to_string_lossy(), target, FetchOptions::new(), CheckoutBuilder::new())
+            .unwrap();
+        let workdir = target.canonicalize().unwrap();
+
+        DeckRepository { repository, deck_root: workdir.clone(), workdir }
+    }
+
+    fn pull(deck_repository: &DeckRepository) -> (&'static str, Vec<String>) {
+        merge_upstream(deck_repository, &mut FetchOptions::new(), signature).unwrap()
+    }
+
+    /// A bare remote seeded with one note, and an author clone that pushes to it
+    fn remote_with_author(dir: &TempDir) -> (PathBuf, DeckRepository) {
+        let remote_path = dir.path().join("remote.git");
+        let remote = Repository::init_bare(&remote_path).unwrap();
+        remote.set_head(&format!("refs/heads/{}", BRANCH)).unwrap();
+
+        let author_path = dir.path().join("author");
+        let mut options = RepositoryInitOptions::new();
+        options.initial_head(BRANCH);
+        let author = Repository::init_opts(&author_path, &options).unwrap();
+        author.remote("origin", &remote_path.to_string_lossy()).unwrap();
+        commit_note(&author, "casa.md", "---\nItalian: casa\nEnglish: [house]\n---\n");
+        push(&author);
+
+        let workdir = author_path.canonicalize().unwrap();
+        (remote_path, DeckRepository { repository: author, deck_root: workdir.clone(), workdir })
+    }
+
+    #[test]
+    fn clones_and_fast_forwards_from_a_local_bare_repository() {
+        let dir = TempDir::new().unwrap();
+        let (remote_path, author) = remote_with_author(&dir);
+
+        let learner_path = dir.path().join("learner");
+        let learner = clone(&remote_path, &learner_path);
+        assert!(learner_path.join("casa.md").is_file());
+
+        assert_eq!(pull(&learner), ("upToDate", Vec::new()));
+
+        commit_note(&author.repository, "gatto.md", "---\nItalian: gatto\nEnglish: [cat]\n---\n");
+        push(&author.repository);
+
+        assert_eq!(pull(&learner), ("fastForward", Vec::new()));
+        assert!(learner_path.join("gatto.md").is_file());
+        assert_eq!(
+            head_commit(&learner.repository).unwrap().unwrap().id(),
+            head_commit(&author.repository).unwrap().unwrap().id()
+        );
+    }
+
+    #[test]
+    fn reports_conflicting_commits_without_touching_the_branch() {
+        let dir = TempDir::new().unwrap();
+        let (remote_path, author) = remote_with_author(&dir);
+        let learner = clone(&remote_path, &dir.path().join("learner"));
+
+        commit_note(&author.repository, "casa.md", "---\nItalian: casa\nEnglish: [home]\n---\n");
+        push(&author.repository);
+        commit_note(&learner.repository, "casa.md", "---\nItalian: casa\nEnglish: [building]\n---\n");
+        let learner_head = head_commit(&learner.repository).unwrap().unwrap().id();
+
+        assert_eq!(pull(&learner), ("conflicts", vec!["casa.md".to_string()]));
+        assert_eq!(head_commit(&learner.repository).unwrap().unwrap().id(), learner_head);
+    }
+
+    #[test]
+    fn reports_local_changes_that_block_a_fast_forward() {
+        let dir = TempDir::new().unwrap();
+        let (remote_path, author) = remote_with_author(&dir);
+        let learner_path = dir.path().join("learner");
+        let learner = clone(&remote_path, &learner_path);
+
+        commit_note(&author.repository, "casa.md", "---\nItalian: casa\nEnglish: [home]\n---\n");
+        push(&author.repository);
+
+        let local_edit = "---\nItalian: casa\nEnglish: [my house]\n---\n";
+        fs::write(learner_path.join("casa.md"), local_edit).unwrap();
+
+        assert_eq!(pull(&learner), ("conflicts", vec!["casa.md".to_string()]));
+        assert_eq!(fs::read_to_string(learner_path.join("casa.md")).unwrap(), local_edit);
+    }
+}
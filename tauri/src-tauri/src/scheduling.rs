@@ -0,0 +1,220 @@
+// scheduling.rs - Spaced repetition state of cards
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::error::AppError;
+use crate::scope;
+use crate::{get_store, save_preferences, PreferenceResult};
+
+/// Store key holding schedules, grouped by deck path and then by card id
+pub(crate) const CARD_SCHEDULES_KEY: &str = "cardSchedules";
+
+/// Store key holding the log of recent reviews
+pub(crate) const REVIEW_HISTORY_KEY: &str = "reviewHistory";
+
+/// Reviews kept in the log; older ones are dropped first. Schedules do not
+/// depend on the log, so trimming it only shortens the visible history.
+const MAX_REVIEW_HISTORY: usize = 5000;
+
+/// Cards with at least this interval are considered mature
+const MATURE_INTERVAL_DAYS: u32 = 21;
+
+/// Ease factor bounds of the SM-2 algorithm
+const DEFAULT_EASE: f32 = 2.5;
+const MINIMUM_EASE: f32 = 1.3;
+
+/// Schedules of one deck, keyed by card id. Card ids may carry a suffix for
+/// derived cards (e.g. cloze deletions) so each one is scheduled on its own.
+pub(crate) type DeckSchedules = BTreeMap<String, CardSchedule>;
+
+/// Spaced repetition state of a single card
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CardSchedule {
+    pub interval_days: u32,
+    pub ease: f32,
+    pub repetitions: u32,
+    pub lapses: u32,
+    pub due: DateTime<Utc>,
+    pub last_reviewed: DateTime<Utc>,
+}
+
+/// A single answered review
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewLog {
+    pub deck_path: String,
+    pub card_id: String,
+    pub grade: u8,
+    pub interval_days: u32,
+    pub reviewed_at: DateTime<Utc>,
+}
+
+/// How well a card is known, derived from its schedule
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Maturity {
+    New,
+    Learning,
+    Young,
+    Mature,
+}
+
+impl Maturity {
+    /// Classifies a card; cards without a schedule have never been reviewed
+    pub(crate) fn of(schedule: Option<&CardSchedule>) -> Self {
+        match schedule {
+            None => Maturity::New,
+            Some(schedule) if schedule.repetitions == 0 => Maturity::Learning,
+            Some(schedule) if schedule.interval_days < MATURE_INTERVAL_DAYS => Maturity::Young,
+            Some(_) => Maturity::Mature,
+        }
+    }
+}
+
+impl CardSchedule {
+    /// Applies an answer using SM-2. Grades: 0 again, 1 hard, 2 good, 3 easy.
+    pub(crate) fn answer(previous: Option<&CardSchedule>, grade: u8, now: DateTime<Utc>) -> Self {
+        let (interval_days, ease, repetitions, lapses) = previous
+            .map(|schedule| (schedule.interval_days, schedule.ease, schedule.repetitions, schedule.lapses))
+            .unwrap_or((0, DEFAULT_EASE, 0, 0));
+
+        // SM-2 quality on its 0-5 scale, where 3 is the lowest passing answer
+        let quality = f32::from(grade) + 2.0;
+        let ease = (ease + 0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02)).max(MINIMUM_EASE);
+
+        let (interval_days, repetitions, lapses) = match grade {
+            0 => (0, 0, lapses + u32::from(repetitions > 0)),
+            _ => {
+                let next_interval = match repetitions {
+                    0 => 1,
+                    1 => 6,
+                    _ => (interval_days as f32 * ease).round() as u32,
+                };
+                let next_interval = match grade {
+                    1 => (next_interval as f32 * 0.8).round().max(1.0) as u32,
+                    3 => (next_interval as f32 * 1.3).round() as u32,
+                    _ => next_interval,
+                };
+                (next_interval, repetitions + 1, lapses)
+            }
+        };
+
+        // Failed cards come back in ten minutes
+        let due = match interval_days {
+            0 => now + Duration::minutes(10),
+            days => now + Duration::days(i64::from(days)),
+        };
+
+        Self {
+            interval_days,
+            ease,
+            repetitions,
+            lapses,
+            due,
+            last_reviewed: now,
+        }
+    }
+}
+
+/// Loads every deck's schedules
+fn load_all_schedules(app: &AppHandle) -> PreferenceResult<BTreeMap<String, DeckSchedules>> {
+    let store = get_store(app)?;
+
+    match store.get(CARD_SCHEDULES_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::storage(format!("Failed to deserialize card schedules: {}", e))),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Loads the schedules of a single deck
+pub(crate) fn load_deck_schedules(app: &AppHandle, deck_path: &str) -> PreferenceResult<DeckSchedules> {
+    Ok(load_all_schedules(app)?.remove(deck_path).unwrap_or_default())
+}
+
+/// Loads the full review log
+pub(crate) fn load_review_history(app: &AppHandle) -> PreferenceResult<Vec<ReviewLog>> {
+    let store = get_store(app)?;
+
+    match store.get(REVIEW_HISTORY_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::storage(format!("Failed to deserialize review history: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Drops the oldest reviews beyond the kept number. The log must be in review order.
+pub(crate) fn trim_review_history(history: &mut Vec<ReviewLog>) {
+    let excess = history.len().saturating_sub(MAX_REVIEW_HISTORY);
+    history.drain(..excess);
+}
+
+/// Records an answer for a card (or derived card) and returns its new schedule
+pub(crate) fn record_review(
+    app: &AppHandle,
+    deck_path: &str,
+    card_id: &str,
+    grade: u8,
+) -> PreferenceResult<CardSchedule> {
+    if grade > 3 {
+        return Err(AppError::invalid("grade", "Grade must be between 0 (again) and 3 (easy)"));
+    }
+
+    if card_id.trim().is_empty() {
+        return Err(AppError::invalid("cardId", "Card id cannot be empty"));
+    }
+
+    let now = Utc::now();
+    let mut all_schedules = load_all_schedules(app)?;
+    let deck_schedules = all_schedules.entry(deck_path.to_string()).or_default();
+
+    let schedule = CardSchedule::answer(deck_schedules.get(card_id), grade, now);
+    deck_schedules.insert(card_id.to_string(), schedule.clone());
+
+    let mut history = load_review_history(app)?;
+    history.push(ReviewLog {
+        deck_path: deck_path.to_string(),
+        card_id: card_id.to_string(),
+        grade,
+        interval_days: schedule.interval_days,
+        reviewed_at: now,
+    });
+    trim_review_history(&mut history);
+
+    let schedules_json = serde_json::to_value(&all_schedules)
+        .map_err(|e| AppError::storage(format!("Failed to serialize card schedules: {}", e)))?;
+    let history_json = serde_json::to_value(&history)
+        .map_err(|e| AppError::storage(format!("Failed to serialize review history: {}", e)))?;
+
+    // Schedule and log are written together so they never disagree
+    save_preferences(app, vec![
+        (CARD_SCHEDULES_KEY.to_string(), schedules_json),
+        (REVIEW_HISTORY_KEY.to_string(), history_json),
+    ])?;
+
+    Ok(schedule)
+}
+
+/// Records the answer given for a card during study
+#[tauri::command(rename_all = "camelCase")]
+pub fn review_card(
+    app: AppHandle,
+    directory_path: String,
+    card_id: String,
+    grade: u8,
+) -> PreferenceResult<CardSchedule> {
+    let deck_path = scope::scoped_deck_path(&app, &directory_path)?;
+
+    record_review(&app, &deck_path.to_string_lossy(), &card_id, grade)
+}
+
+/// Retrieves the schedules of every reviewed card in a deck
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_card_schedules(app: AppHandle, directory_path: String) -> PreferenceResult<DeckSchedules> {
+    let deck_path = scope::scoped_deck_path(&app, &directory_path)?;
+
+    load_deck_schedules(&app, &deck_path.to_string_lossy())
+}
@@ -0,0 +1,311 @@
+// scope.rs - Allow-list of folders and files the user opened through native dialogs
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+use chrono::{DateTime, Utc};
+
+use crate::error::AppError;
+use crate::{get_store, save_store, PreferenceResult};
+
+/// Store key holding the granted scopes. Only written by the backend.
+pub(crate) const ALLOWED_SCOPES_KEY: &str = "allowedScopes";
+
+/// What a scope grants access to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScopeKind {
+    /// A folder and everything below it
+    Directory,
+    /// A single file, e.g. a preferences export
+    File,
+}
+
+/// A path the user picked in a dialog
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowedScope {
+    /// Canonical path
+    pub path: String,
+    pub kind: ScopeKind,
+    pub granted_at: DateTime<Utc>,
+}
+
+/// Why a path was refused
+#[derive(Debug)]
+pub enum ScopeError {
+    /// The path is not inside any granted scope
+    OutOfScope(PathBuf),
+    /// The path looks like it is inside a scope but leaves it through `..` or a symlink
+    Escape(PathBuf),
+    /// The path cannot be checked at all
+    Invalid { path: String, reason: String },
+}
+
+impl fmt::Display for ScopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScopeError::OutOfScope(path) => write!(f, "Access to '{}' was not granted; open it first", path.display()),
+            ScopeError::Escape(path) => write!(f, "Path '{}' leaves the folder it was opened from", path.display()),
+            ScopeError::Invalid { path, reason } => write!(f, "Invalid path '{}': {}", path, reason),
+        }
+    }
+}
+
+impl std::error::Error for ScopeError {}
+
+/// Loads the granted scopes
+fn load_scopes(app: &AppHandle) -> PreferenceResult<Vec<AllowedScope>> {
+    let store = get_store(app)?;
+
+    match store.get(ALLOWED_SCOPES_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::storage(format!("Failed to deserialize allowed scopes: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Persists the granted scopes. Bypasses `save_preference`, which refuses this key.
+fn save_scopes(app: &AppHandle, scopes: &[AllowedScope]) -> PreferenceResult<()> {
+    let store = get_store(app)?;
+
+    let scopes_json = serde_json::to_value(scopes)
+        .map_err(|e| AppError::storage(format!("Failed to serialize allowed scopes: {}", e)))?;
+
+    store.set(ALLOWED_SCOPES_KEY, scopes_json);
+    save_store(app, &store)
+}
+
+/// Resolves a path from the webview to its canonical form. Paths that do not
+/// exist yet (export targets) are resolved through their parent folder.
+fn resolve(path: &str) -> Result<PathBuf, ScopeError> {
+    let invalid = |reason: &str| ScopeError::Invalid { path: path.to_string(), reason: reason.to_string() };
+
+    if path.trim().is_empty() {
+        return Err(invalid("path cannot be empty"));
+    }
+
+    let requested = Path::new(path);
+    if !requested.is_absolute() {
+        return Err(invalid("path must be absolute"));
+    }
+
+    if requested.components().any(|component| component == Component::ParentDir) {
+        return Err(ScopeError::Escape(requested.to_path_buf()));
+    }
+
+    if let Ok(canonical) = fs::canonicalize(requested) {
+        return Ok(canonical);
+    }
+
+    let (Some(parent), Some(file_name)) = (requested.parent(), requested.file_name()) else {
+        return Err(invalid("path has no parent folder"));
+    };
+
+    let parent = fs::canonicalize(parent)
+        .map_err(|e| invalid(&format!("folder does not exist: {}", e)))?;
+
+    Ok(parent.join(file_name))
+}
+
+/// Whether a canonical path is covered by a scope
+fn covers(scope: &AllowedScope, path: &Path) -> bool {
+    match scope.kind {
+        ScopeKind::Directory => path.starts_with(&scope.path),
+        ScopeKind::File => path == Path::new(&scope.path),
+    }
+}
+
+/// Checks a path against the granted scopes, returning its canonical form
+pub(crate) fn check_path(app: &AppHandle, path: &str) -> PreferenceResult<PathBuf> {
+    let resolved = resolve(path)?;
+    let scopes = load_scopes(app)?;
+
+    if scopes.iter().any(|scope| covers(scope, &resolved)) {
+        return Ok(resolved);
+    }
+
+    // Lexically inside a scope but not once symlinks are resolved
+    let requested = Path::new(path);
+    if scopes.iter().any(|scope| covers(scope, requested)) {
+        return Err(ScopeError::Escape(requested.to_path_buf()).into());
+    }
+
+    Err(ScopeError::OutOfScope(resolved).into())
+}
+
+/// Checks a deck folder against the granted scopes, returning its canonical path
+pub(crate) fn scoped_deck_path(app: &AppHandle, directory_path: &str) -> PreferenceResult<PathBuf> {
+    let path = check_path(app, directory_path)?;
+
+    if !path.is_dir() {
+        return Err(AppError::invalid("directoryPath", format!("Path '{}' is not a directory", directory_path)));
+    }
+
+    Ok(path)
+}
+
+/// Records a path picked by the user. Paths already covered by a folder scope are not added again.
+pub(crate) fn grant_scope(app: &AppHandle, path: &Path, kind: ScopeKind) -> PreferenceResult<PathBuf> {
+    let path = match kind {
+        ScopeKind::Directory => fs::canonicalize(path)
+            .map_err(|e| AppError::not_found(format!("Directory '{}' does not exist: {}", path.display(), e)))?,
+        ScopeKind::File => resolve(&path.to_string_lossy())?,
+    };
+
+    let mut scopes = load_scopes(app)?;
+    if scopes.iter().any(|scope| scope.kind == ScopeKind::Directory && covers(scope, &path)) {
+        return Ok(path);
+    }
+
+    // A new folder scope replaces the scopes it contains
+    if kind == ScopeKind::Directory {
+        scopes.retain(|scope| !Path::new(&scope.path).starts_with(&path));
+    }
+
+    scopes.push(AllowedScope {
+        path: path.to_string_lossy().to_string(),
+        kind,
+        granted_at: Utc::now(),
+    });
+    scopes.sort_by(|scope_a, scope_b| scope_a.path.cmp(&scope_b.path));
+    save_scopes(app, &scopes)?;
+
+    Ok(path)
+}
+
+/// Keeps the granted scopes across a bulk preference replacement,
+/// so an imported or webview-provided store cannot grant access
+pub(crate) fn preserve_scopes(previous: Option<Value>, preferences: &mut serde_json::Map<String, Value>) {
+    match previous {
+        Some(scopes) => preferences.insert(ALLOWED_SCOPES_KEY.to_string(), scopes),
+        None => preferences.remove(ALLOWED_SCOPES_KEY),
+    };
+}
+
+/// Opens a folder picker and grants access to the chosen folder
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn pick_directory(app: AppHandle, title: Option<String>) -> PreferenceResult<Option<String>> {
+    let mut dialog = app.dialog().file();
+    if let Some(title) = title {
+        dialog = dialog.set_title(title);
+    }
+
+    let Some(picked) = dialog.blocking_pick_folder() else {
+        return Ok(None);
+    };
+
+    let path = picked.into_path()
+        .map_err(|e| AppError::internal(format!("Failed to resolve the selected folder: {}", e)))?;
+    let granted = grant_scope(&app, &path, ScopeKind::Directory)?;
+
+    Ok(Some(granted.to_string_lossy().to_string()))
+}
+
+/// Kind of file picked for an import or export
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TransferFile {
+    /// Raw preferences as JSON
+    #[default]
+    Preferences,
+    /// Zip archive of the whole learning profile
+    ProfileArchive,
+}
+
+impl TransferFile {
+    fn title(self, export: bool) -> &'static str {
+        match (self, export) {
+            (TransferFile::Preferences, false) => "Import preferences",
+            (TransferFile::Preferences, true) => "Export preferences",
+            (TransferFile::ProfileArchive, false) => "Import learning profile",
+            (TransferFile::ProfileArchive, true) => "Export learning profile",
+        }
+    }
+
+    fn filter(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            TransferFile::Preferences => ("JSON", &["json"]),
+            TransferFile::ProfileArchive => ("Profile archive", &["zip"]),
+        }
+    }
+
+    fn default_file_name(self) -> &'static str {
+        match self {
+            TransferFile::Preferences => "preferences.json",
+            TransferFile::ProfileArchive => "profile.zip",
+        }
+    }
+}
+
+/// Opens a file picker for an import and grants read access to the chosen file
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn pick_import_file(app: AppHandle, kind: Option<TransferFile>) -> PreferenceResult<Option<String>> {
+    let kind = kind.unwrap_or_default();
+    let (filter_name, extensions) = kind.filter();
+
+    let picked = app.dialog().file()
+        .set_title(kind.title(false))
+        .add_filter(filter_name, extensions)
+        .blocking_pick_file();
+
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+
+    let path = picked.into_path()
+        .map_err(|e| AppError::internal(format!("Failed to resolve the selected file: {}", e)))?;
+    let granted = grant_scope(&app, &path, ScopeKind::File)?;
+
+    Ok(Some(granted.to_string_lossy().to_string()))
+}
+
+/// Opens a save dialog for an export and grants write access to the chosen file
+#[tauri::command(async, rename_all = "camelCase")]
+pub fn pick_export_file(
+    app: AppHandle,
+    kind: Option<TransferFile>,
+    file_name: Option<String>,
+) -> PreferenceResult<Option<String>> {
+    let kind = kind.unwrap_or_default();
+    let (filter_name, extensions) = kind.filter();
+
+    let picked = app.dialog().file()
+        .set_title(kind.title(true))
+        .add_filter(filter_name, extensions)
+        .set_file_name(file_name.unwrap_or_else(|| kind.default_file_name().to_string()))
+        .blocking_save_file();
+
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+
+    let path = picked.into_path()
+        .map_err(|e| AppError::internal(format!("Failed to resolve the selected file: {}", e)))?;
+    let granted = grant_scope(&app, &path, ScopeKind::File)?;
+
+    Ok(Some(granted.to_string_lossy().to_string()))
+}
+
+/// Lists the folders and files the user granted access to
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_allowed_scopes(app: AppHandle) -> PreferenceResult<Vec<AllowedScope>> {
+    load_scopes(&app)
+}
+
+/// Revokes access to a previously granted folder or file
+#[tauri::command(rename_all = "camelCase")]
+pub fn revoke_scope(app: AppHandle, path: String) -> PreferenceResult<()> {
+    let mut scopes = load_scopes(&app)?;
+    let count = scopes.len();
+
+    scopes.retain(|scope| scope.path != path);
+    if scopes.len() == count {
+        return Err(AppError::not_found(format!("No access was granted to '{}'", path)));
+    }
+
+    save_scopes(&app, &scopes)
+}
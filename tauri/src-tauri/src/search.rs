@@ -0,0 +1,304 @@
+// search.rs - Full-text search over deck cards
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::cloze;
+use crate::error::AppError;
+use crate::index::{self, CardRecord, DeckSnapshot};
+use crate::scheduling::{self, Maturity};
+use crate::text::{fold, normalize_headword};
+use crate::PreferenceResult;
+
+/// Weight of a match when the query term is only a prefix of the indexed term
+const PREFIX_MATCH_WEIGHT: f32 = 0.5;
+
+/// Bonus for cards whose headword equals the whole query
+const EXACT_HEADWORD_BONUS: f32 = 10.0;
+
+/// Number of results returned when no limit is given
+const DEFAULT_RESULT_LIMIT: usize = 50;
+
+/// Card fields that are indexed, in decreasing order of importance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Field {
+    Italian,
+    English,
+    Tags,
+    Body,
+}
+
+impl Field {
+    fn boost(self) -> f32 {
+        match self {
+            Field::Italian => 3.0,
+            Field::English => 2.0,
+            Field::Tags => 1.5,
+            Field::Body => 1.0,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Italian => "italian",
+            Field::English => "english",
+            Field::Tags => "tags",
+            Field::Body => "body",
+        }
+    }
+}
+
+/// Occurrences of a term in one field of one card
+#[derive(Debug)]
+struct Posting {
+    card: usize,
+    field: Field,
+    frequency: u32,
+}
+
+/// Inverted index over the cards of one deck
+pub(crate) struct SearchIndex {
+    fingerprint: String,
+    cards: Vec<CardRecord>,
+    headwords: Vec<Vec<String>>,
+    postings: BTreeMap<String, Vec<Posting>>,
+}
+
+/// Search indexes kept in memory per deck, rebuilt when the deck changes
+#[derive(Default)]
+pub struct SearchState {
+    indexes: Mutex<HashMap<String, Arc<SearchIndex>>>,
+}
+
+impl SearchState {
+    /// Drops every cached index
+    pub(crate) fn clear(&self) -> PreferenceResult<()> {
+        self.indexes.lock()
+            .map_err(|_| AppError::internal("Search index lock was poisoned"))?
+            .clear();
+
+        Ok(())
+    }
+}
+
+/// Optional restrictions applied to search results
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchFilters {
+    /// Cards must carry every one of these tags
+    tags: Vec<String>,
+    /// Folder relative to the deck root
+    folder: Option<String>,
+    /// Accepted maturities; empty accepts all
+    maturity: Vec<Maturity>,
+    limit: Option<usize>,
+}
+
+/// A card matching a search, best matches first
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    card: CardRecord,
+    score: f32,
+    matched_fields: Vec<&'static str>,
+    maturity: Maturity,
+}
+
+/// Splits text into accent-insensitive, lowercase terms
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    fold(text)
+        .split(|character: char| !character.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl SearchIndex {
+    /// Indexes every card of a deck snapshot
+    pub(crate) fn build(snapshot: &DeckSnapshot) -> Self {
+        let mut postings: BTreeMap<String, Vec<Posting>> = BTreeMap::new();
+
+        for (card_index, card) in snapshot.cards.iter().enumerate() {
+            let fields = [
+                (Field::Italian, card.italian_forms.join(" ")),
+                (Field::English, card.english.join(" ")),
+                (Field::Tags, card.tags.join(" ")),
+                (Field::Body, cloze::reveal(&card.body)),
+            ];
+
+            for (field, text) in fields {
+                let mut frequencies: HashMap<String, u32> = HashMap::new();
+                for term in tokenize(&text) {
+                    *frequencies.entry(term).or_default() += 1;
+                }
+
+                for (term, frequency) in frequencies {
+                    postings.entry(term).or_default().push(Posting {
+                        card: card_index,
+                        field,
+                        frequency,
+                    });
+                }
+            }
+        }
+
+        Self {
+            fingerprint: snapshot.fingerprint.clone(),
+            headwords: snapshot.cards.iter()
+                .map(|card| card.italian_forms.iter().map(|form| normalize_headword(form)).collect())
+                .collect(),
+            cards: snapshot.cards.clone(),
+            postings,
+        }
+    }
+
+    /// Indexed terms matching a query term exactly or by prefix, with their weight
+    fn matching_terms<'a>(&'a self, query_term: &'a str) -> impl Iterator<Item = (&'a Vec<Posting>, f32)> + 'a {
+        self.postings
+            .range(query_term.to_string()..)
+            .take_while(move |(term, _)| term.starts_with(query_term))
+            .map(move |(term, postings)| {
+                let weight = if term == query_term { 1.0 } else { PREFIX_MATCH_WEIGHT };
+                (postings, weight)
+            })
+    }
+
+    /// Ranks cards matching every query term; an empty query lists all cards
+    pub(crate) fn search<F>(&self, query: &str, accept: F) -> Vec<(usize, f32, Vec<&'static str>)>
+    where
+        F: Fn(&CardRecord) -> bool,
+    {
+        let query_terms = tokenize(query);
+
+        if query_terms.is_empty() {
+            let mut all: Vec<(usize, f32, Vec<&'static str>)> = (0..self.cards.len())
+                .filter(|&card_index| accept(&self.cards[card_index]))
+                .map(|card_index| (card_index, 0.0, Vec::new()))
+                .collect();
+            all.sort_by(|(card_a, ..), (card_b, ..)| fold(&self.cards[*card_a].italian).cmp(&fold(&self.cards[*card_b].italian)));
+            return all;
+        }
+
+        let card_count = self.cards.len() as f32;
+
+        // Best score of every query term per card
+        let mut term_scores: HashMap<usize, Vec<f32>> = HashMap::new();
+        let mut matched_fields: HashMap<usize, HashSet<Field>> = HashMap::new();
+
+        for (term_position, query_term) in query_terms.iter().enumerate() {
+            for (postings, weight) in self.matching_terms(query_term) {
+                let document_frequency = postings.iter()
+                    .map(|posting| posting.card)
+                    .collect::<HashSet<_>>()
+                    .len() as f32;
+                let inverse_frequency = (1.0 + card_count / document_frequency).ln();
+
+                for posting in postings {
+                    let score = posting.field.boost()
+                        * inverse_frequency
+                        * (1.0 + (posting.frequency as f32).ln())
+                        * weight;
+
+                    let scores = term_scores.entry(posting.card)
+                        .or_insert_with(|| vec![0.0; query_terms.len()]);
+                    scores[term_position] = scores[term_position].max(score);
+
+                    matched_fields.entry(posting.card).or_default().insert(posting.field);
+                }
+            }
+        }
+
+        let normalized_query = normalize_headword(query);
+
+        let mut hits: Vec<(usize, f32, Vec<&'static str>)> = term_scores.into_iter()
+            .filter(|(_, scores)| scores.iter().all(|score| *score > 0.0))
+            .filter(|(card_index, _)| accept(&self.cards[*card_index]))
+            .map(|(card_index, scores)| {
+                let mut score: f32 = scores.iter().sum();
+                if self.headwords[card_index].contains(&normalized_query) {
+                    score += EXACT_HEADWORD_BONUS;
+                }
+
+                let mut fields: Vec<Field> = matched_fields.remove(&card_index)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect();
+                fields.sort_by(|field_a, field_b| field_b.boost().total_cmp(&field_a.boost()));
+
+                (card_index, score, fields.into_iter().map(Field::name).collect())
+            })
+            .collect();
+
+        hits.sort_by(|(card_a, score_a, _), (card_b, score_b, _)| {
+            score_b.total_cmp(score_a)
+                .then_with(|| self.cards[*card_a].id.cmp(&self.cards[*card_b].id))
+        });
+
+        hits
+    }
+}
+
+/// Returns the cached index of a deck, rebuilding it when any card changed
+fn deck_search_index(state: &SearchState, snapshot: &DeckSnapshot) -> PreferenceResult<Arc<SearchIndex>> {
+    let mut indexes = state.indexes.lock()
+        .map_err(|_| AppError::internal("Search index lock was poisoned"))?;
+
+    if let Some(search_index) = indexes.get(&snapshot.directory_path) {
+        if search_index.fingerprint == snapshot.fingerprint {
+            return Ok(search_index.clone());
+        }
+    }
+
+    let search_index = Arc::new(SearchIndex::build(snapshot));
+    indexes.insert(snapshot.directory_path.clone(), search_index.clone());
+
+    Ok(search_index)
+}
+
+/// Searches Italian forms, translations, tags and note bodies of a deck.
+/// Matching ignores case and accents, and the query terms also match as prefixes.
+#[tauri::command(rename_all = "camelCase")]
+pub fn search_cards(
+    app: AppHandle,
+    state: State<'_, SearchState>,
+    directory_path: String,
+    query: String,
+    filters: Option<SearchFilters>,
+) -> PreferenceResult<Vec<SearchHit>> {
+    let filters = filters.unwrap_or_default();
+    let snapshot = index::load_deck(&app, &directory_path, false)?;
+    let search_index = deck_search_index(&state, &snapshot)?;
+    let schedules = scheduling::load_deck_schedules(&app, &snapshot.directory_path)?;
+
+    let required_tags: Vec<String> = filters.tags.iter().map(|tag| fold(tag.trim())).collect();
+    let folder_prefix = filters.folder.as_deref()
+        .map(|folder| folder.trim_matches('/'))
+        .filter(|folder| !folder.is_empty())
+        .map(|folder| format!("{}/", folder));
+
+    let accept = |card: &CardRecord| {
+        let card_tags: Vec<String> = card.tags.iter().map(|tag| fold(tag)).collect();
+
+        required_tags.iter().all(|tag| card_tags.contains(tag))
+            && folder_prefix.as_ref().map_or(true, |prefix| card.id.starts_with(prefix))
+            && (filters.maturity.is_empty()
+                || filters.maturity.contains(&Maturity::of(schedules.get(&card.id))))
+    };
+
+    let hits = search_index.search(&query, accept)
+        .into_iter()
+        .take(filters.limit.unwrap_or(DEFAULT_RESULT_LIMIT))
+        .map(|(card_index, score, matched_fields)| {
+            let card = search_index.cards[card_index].clone();
+            SearchHit {
+                maturity: Maturity::of(schedules.get(&card.id)),
+                card,
+                score,
+                matched_fields,
+            }
+        })
+        .collect();
+
+    Ok(hits)
+}
@@ -0,0 +1,282 @@
+// storage.rs - Crash-safe writes of the preference store with rolling backups
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+
+use crate::error::AppError;
+use crate::migrations;
+use crate::profiles;
+use crate::{emit_to_frontend, get_store, preserve_backend_keys, PreferenceResult};
+
+/// Folder next to the store holding its backups
+const BACKUP_DIR_NAME: &str = "backups";
+
+/// Most recent backups always kept
+const RECENT_BACKUPS: usize = 10;
+
+/// Days for which the newest backup of each day is kept
+const DAILY_BACKUP_DAYS: i64 = 30;
+
+/// Minimum time between automatic backups, so a burst of changes
+/// does not push every older backup out of the recent set
+const BACKUP_INTERVAL_MINUTES: i64 = 10;
+
+/// Timestamp in backup file names, e.g. `store-20240131T084500123Z.json`
+const BACKUP_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// A backup of the preference store
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub created_at: DateTime<Utc>,
+    pub size: u64,
+    /// Whether the backup holds a readable store
+    pub valid: bool,
+}
+
+/// Location of the active profile's store file
+fn store_path(app: &AppHandle) -> PreferenceResult<PathBuf> {
+    let data_dir = app.path().app_data_dir()
+        .map_err(|e| AppError::storage(format!("Failed to resolve app data directory: {}", e)))?;
+
+    Ok(data_dir.join(profiles::active_store_file(app)?))
+}
+
+/// Folder holding the store backups
+fn backup_dir(app: &AppHandle) -> PreferenceResult<PathBuf> {
+    let path = store_path(app)?;
+
+    Ok(path.with_file_name(BACKUP_DIR_NAME))
+}
+
+/// Reads a store file, failing unless it holds a JSON object
+fn read_store_file(path: &Path) -> PreferenceResult<Map<String, Value>> {
+    let bytes = fs::read(path)
+        .map_err(|e| AppError::io(path, format!("Failed to read store '{}': {}", path.display(), e)))?;
+
+    serde_json::from_slice(&bytes)
+        .map_err(|e| AppError::parse(path, Some(e.line()), format!("Store '{}' is corrupt: {}", path.display(), e)))
+}
+
+/// Writes a file through a temporary sibling that is flushed to disk and
+/// renamed into place, so readers see either the old or the new content
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> PreferenceResult<()> {
+    let parent = path.parent()
+        .ok_or_else(|| AppError::invalid("path", format!("Path '{}' has no parent folder", path.display())))?;
+
+    fs::create_dir_all(parent)
+        .map_err(|e| AppError::io(parent, format!("Failed to create folder '{}': {}", parent.display(), e)))?;
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut file = File::create(&temp_path)
+        .map_err(|e| AppError::io(&temp_path, format!("Failed to create '{}': {}", temp_path.display(), e)))?;
+    file.write_all(bytes)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| AppError::io(&temp_path, format!("Failed to write '{}': {}", temp_path.display(), e)))?;
+    drop(file);
+
+    fs::rename(&temp_path, path)
+        .map_err(|e| AppError::io(path, format!("Failed to replace '{}': {}", path.display(), e)))?;
+
+    // Persist the rename itself; directories cannot be opened for syncing on Windows
+    #[cfg(unix)]
+    if let Ok(directory) = File::open(parent) {
+        let _ = directory.sync_all();
+    }
+
+    Ok(())
+}
+
+/// Backups in the folder with their timestamps, newest first
+fn backup_files(directory: &Path) -> Vec<(PathBuf, DateTime<Utc>)> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<(PathBuf, DateTime<Utc>)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let timestamp = file_name.strip_prefix("store-")?.strip_suffix(".json")?;
+            let created_at = NaiveDateTime::parse_from_str(timestamp, BACKUP_TIME_FORMAT).ok()?.and_utc();
+
+            Some((entry.path(), created_at))
+        })
+        .collect();
+
+    backups.sort_by_key(|(_, created_at)| std::cmp::Reverse(*created_at));
+    backups
+}
+
+/// Deletes backups outside the recent set that are not the newest of a recent day
+fn prune_backups(directory: &Path) {
+    let cutoff = Utc::now() - Duration::days(DAILY_BACKUP_DAYS);
+    let mut kept_days = Vec::new();
+
+    for (index, (path, created_at)) in backup_files(directory).into_iter().enumerate() {
+        let day = created_at.date_naive();
+        let newest_of_day = !kept_days.contains(&day);
+        kept_days.push(day);
+
+        if index < RECENT_BACKUPS || (newest_of_day && created_at >= cutoff) {
+            continue;
+        }
+
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Copies the current store into the backup folder. Missing or corrupt
+/// stores are not backed up, so they never replace a good backup.
+pub(crate) fn create_backup(app: &AppHandle) -> PreferenceResult<Option<PathBuf>> {
+    let path = store_path(app)?;
+
+    if !path.exists() || read_store_file(&path).is_err() {
+        return Ok(None);
+    }
+
+    let bytes = fs::read(&path)
+        .map_err(|e| AppError::io(&path, format!("Failed to read store '{}': {}", path.display(), e)))?;
+
+    let directory = backup_dir(app)?;
+    let backup_path = directory.join(format!("store-{}.json", Utc::now().format(BACKUP_TIME_FORMAT)));
+    write_atomic(&backup_path, &bytes)?;
+    prune_backups(&directory);
+
+    Ok(Some(backup_path))
+}
+
+/// Backs up the current store unless the newest backup is recent enough
+fn backup_if_due(app: &AppHandle) -> PreferenceResult<()> {
+    let newest = backup_files(&backup_dir(app)?).first().map(|(_, created_at)| *created_at);
+
+    if newest.is_some_and(|created_at| Utc::now() - created_at < Duration::minutes(BACKUP_INTERVAL_MINUTES)) {
+        return Ok(());
+    }
+
+    create_backup(app).map(|_| ())
+}
+
+/// Opens the active profile's store without the plugin's debounced auto-save;
+/// every write goes through `write_store`
+pub(crate) fn open_store(app: &AppHandle) -> PreferenceResult<std::sync::Arc<tauri_plugin_store::Store<tauri::Wry>>> {
+    let store_file = profiles::active_store_file(app)?;
+
+    if let Some(store) = app.get_store(&store_file) {
+        return Ok(store);
+    }
+
+    tauri_plugin_store::StoreBuilder::new(app, &store_file)
+        .disable_auto_save()
+        .build()
+        .map_err(|e| AppError::storage(format!("Failed to access store '{}': {}", store_file.display(), e)))
+}
+
+/// Writes the in-memory store to disk atomically, backing up the previous file first
+pub(crate) fn write_store(app: &AppHandle, store: &tauri_plugin_store::Store<tauri::Wry>) -> PreferenceResult<()> {
+    backup_if_due(app)?;
+
+    let entries: Map<String, Value> = store.entries().into_iter().collect();
+    let bytes = serde_json::to_vec_pretty(&Value::Object(entries))
+        .map_err(|e| AppError::storage(format!("Failed to serialize store: {}", e)))?;
+
+    write_atomic(&store_path(app)?, &bytes)
+}
+
+/// Checks the store file before it is opened. A corrupt store is moved aside
+/// and replaced by the newest valid backup, and the frontend is told.
+pub(crate) fn recover_store(app: &AppHandle) -> PreferenceResult<()> {
+    let path = store_path(app)?;
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    if read_store_file(&path).is_ok() {
+        return backup_if_due(app);
+    }
+
+    let directory = backup_dir(app)?;
+    fs::create_dir_all(&directory)
+        .map_err(|e| AppError::io(&directory, format!("Failed to create folder '{}': {}", directory.display(), e)))?;
+
+    let corrupt_path = directory.join(format!("corrupt-{}.json", Utc::now().format(BACKUP_TIME_FORMAT)));
+    fs::rename(&path, &corrupt_path)
+        .map_err(|e| AppError::io(&path, format!("Failed to move corrupt store aside: {}", e)))?;
+
+    let restored = backup_files(&directory)
+        .into_iter()
+        .find(|(backup_path, _)| read_store_file(backup_path).is_ok());
+
+    if let Some((backup_path, _)) = &restored {
+        let bytes = fs::read(backup_path)
+            .map_err(|e| AppError::io(backup_path, format!("Failed to read backup '{}': {}", backup_path.display(), e)))?;
+        write_atomic(&path, &bytes)?;
+    }
+
+    let file_name = |path: &Path| path.file_name().map(|name| name.to_string_lossy().to_string());
+    let payload = json!({
+        "corruptCopy": file_name(&corrupt_path),
+        "restoredFrom": restored.as_ref().and_then(|(backup_path, _)| file_name(backup_path))
+    });
+    emit_to_frontend(app, "store-recovered", payload)
+}
+
+/// Lists store backups, newest first
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_backups(app: AppHandle) -> PreferenceResult<Vec<BackupInfo>> {
+    let backups = backup_files(&backup_dir(&app)?)
+        .into_iter()
+        .map(|(path, created_at)| BackupInfo {
+            file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            size: fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0),
+            valid: read_store_file(&path).is_ok(),
+            created_at,
+        })
+        .collect();
+
+    Ok(backups)
+}
+
+/// Replaces the store with a backup. The current store is backed up first
+/// so the restore can be undone.
+#[tauri::command(rename_all = "camelCase")]
+pub fn restore_backup(app: AppHandle, file_name: String) -> PreferenceResult<()> {
+    let directory = backup_dir(&app)?;
+    let backup_path = backup_files(&directory)
+        .into_iter()
+        .map(|(path, _)| path)
+        .find(|path| path.file_name().is_some_and(|name| name.to_string_lossy() == file_name))
+        .ok_or_else(|| AppError::not_found(format!("Backup '{}' does not exist", file_name)))?;
+
+    // Only a readable backup replaces the store
+    let mut preferences = read_store_file(&backup_path)?;
+
+    // Scopes revoked since the backup stay revoked, and quarantined values stay pending
+    let store = get_store(&app)?;
+    preserve_backend_keys(&store, &mut preferences);
+
+    let bytes = serde_json::to_vec_pretty(&Value::Object(preferences))
+        .map_err(|e| AppError::storage(format!("Failed to serialize backup '{}': {}", file_name, e)))?;
+
+    create_backup(&app)?;
+    write_atomic(&store_path(&app)?, &bytes)?;
+
+    store.reload()
+        .map_err(|e| AppError::storage(format!("Failed to reload store: {}", e)))?;
+
+    // Backups from older versions are upgraded like a store opened at startup
+    migrations::upgrade_store(&app)?;
+
+    let preferences: Map<String, Value> = store.entries().into_iter().collect();
+    emit_to_frontend(&app, "preferences-updated", Value::Object(preferences))
+}
@@ -0,0 +1,166 @@
+// study.rs - Study sessions drawn from decks and filtered decks
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use chrono::{DateTime, Utc};
+
+use crate::cloze::{self, ClozeCard};
+use crate::decks;
+use crate::filtered_decks;
+use crate::index::{self, CardRecord};
+use crate::scheduling::{self, CardSchedule, DeckSchedules, Maturity};
+use crate::PreferenceResult;
+
+/// New cards introduced per session when no limit is given
+const DEFAULT_NEW_LIMIT: usize = 20;
+
+/// Reviews per session when no limit is given
+const DEFAULT_REVIEW_LIMIT: usize = 200;
+
+/// Where the cards of a session come from
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum StudySource {
+    Deck {
+        #[serde(rename = "directoryPath")]
+        directory_path: String,
+    },
+    Filtered {
+        id: String,
+    },
+}
+
+/// A card to study, answered through `review_card` with its deck path and card id
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StudyItem {
+    pub deck_path: String,
+    pub card_id: String,
+    pub card: CardRecord,
+    /// Set for cloze cards derived from the note body
+    pub cloze: Option<ClozeCard>,
+    pub schedule: Option<CardSchedule>,
+    pub maturity: Maturity,
+}
+
+/// Cards of a study session in the order they should be shown
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StudySession {
+    pub name: String,
+    pub deck_path: String,
+    pub items: Vec<StudyItem>,
+    pub review_count: usize,
+    pub new_count: usize,
+}
+
+/// Study items of a note and, when requested, of its cloze cards
+pub(crate) fn note_items(
+    deck_path: &str,
+    card: CardRecord,
+    schedules: &DeckSchedules,
+    include_clozes: bool,
+) -> Vec<StudyItem> {
+    let item = |card_id: String, card: CardRecord, cloze: Option<ClozeCard>| {
+        let schedule = schedules.get(&card_id).cloned();
+        StudyItem {
+            deck_path: deck_path.to_string(),
+            card_id,
+            maturity: Maturity::of(schedule.as_ref()),
+            card,
+            cloze,
+            schedule,
+        }
+    };
+
+    let clozes = match include_clozes {
+        true => cloze::cloze_cards(&card),
+        false => Vec::new(),
+    };
+
+    let mut items: Vec<StudyItem> = clozes.into_iter()
+        .map(|cloze| item(cloze.card_id.clone(), card.clone(), Some(cloze)))
+        .collect();
+    items.insert(0, item(card.id.clone(), card, None));
+
+    items
+}
+
+/// Orders items for a session: due reviews (most overdue first), then new cards.
+/// With `study_ahead`, cards not due yet follow, soonest first.
+pub(crate) fn session_items(
+    items: Vec<StudyItem>,
+    new_limit: usize,
+    review_limit: usize,
+    study_ahead: bool,
+    now: DateTime<Utc>,
+) -> Vec<StudyItem> {
+    let mut due = Vec::new();
+    let mut new = Vec::new();
+    let mut ahead = Vec::new();
+
+    for item in items {
+        match &item.schedule {
+            None => new.push(item),
+            Some(schedule) if schedule.due <= now => due.push(item),
+            Some(_) if study_ahead => ahead.push(item),
+            Some(_) => {}
+        }
+    }
+
+    let due_date = |item: &StudyItem| item.schedule.as_ref().map(|schedule| schedule.due);
+    due.sort_by_key(due_date);
+    ahead.sort_by_key(due_date);
+
+    let review_count = due.len().min(review_limit);
+
+    due.into_iter()
+        .take(review_limit)
+        .chain(new.into_iter().take(new_limit))
+        .chain(ahead.into_iter().take(review_limit - review_count))
+        .collect()
+}
+
+/// Builds the queue for a study session. Regular decks contain due and new
+/// cards; filtered decks contain every selected card, so they can be used to
+/// study ahead of schedule. Cloze cards are included unless disabled.
+#[tauri::command(rename_all = "camelCase")]
+pub fn start_study_session(
+    app: AppHandle,
+    source: StudySource,
+    new_limit: Option<usize>,
+    review_limit: Option<usize>,
+    include_clozes: Option<bool>,
+) -> PreferenceResult<StudySession> {
+    let new_limit = new_limit.unwrap_or(DEFAULT_NEW_LIMIT);
+    let review_limit = review_limit.unwrap_or(DEFAULT_REVIEW_LIMIT);
+
+    let (name, deck_path, cards, schedules, study_ahead) = match source {
+        StudySource::Deck { directory_path } => {
+            let snapshot = index::load_deck(&app, &directory_path, false)?;
+            let schedules = scheduling::load_deck_schedules(&app, &snapshot.directory_path)?;
+            let name = decks::deck_display_name(&app, &snapshot.directory_path)?;
+
+            (name, snapshot.directory_path, snapshot.cards, schedules, false)
+        }
+        StudySource::Filtered { id } => {
+            let deck = filtered_decks::find_filtered_deck(&app, &id)?;
+            let (cards, schedules) = filtered_decks::filtered_deck_cards(&app, &deck)?;
+
+            (deck.name, deck.deck_path, cards, schedules, true)
+        }
+    };
+
+    let items = cards.into_iter()
+        .flat_map(|card| note_items(&deck_path, card, &schedules, include_clozes.unwrap_or(true)))
+        .collect();
+    let items = session_items(items, new_limit, review_limit, study_ahead, Utc::now());
+    let new_count = items.iter().filter(|item| item.schedule.is_none()).count();
+
+    Ok(StudySession {
+        name,
+        deck_path,
+        review_count: items.len() - new_count,
+        new_count,
+        items,
+    })
+}
@@ -0,0 +1,87 @@
+// text.rs - Text normalization shared by duplicate detection, search and form validation
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Italian articles stripped from the start of a headword
+const ITALIAN_ARTICLES: &[&str] = &["il ", "lo ", "la ", "i ", "gli ", "le ", "un ", "uno ", "una "];
+
+/// Elided articles, written without a space before the noun
+const ELIDED_ARTICLES: &[&str] = &["l'", "un'", "l’", "un’"];
+
+/// English words that do not change the meaning of a translation
+const ENGLISH_PREFIXES: &[&str] = &["to ", "the ", "a ", "an "];
+
+/// Lowercases and removes accents ("Perché" -> "perche")
+pub(crate) fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|character| !is_combining_mark(*character))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Composes Unicode (NFC), trims and collapses runs of whitespace, so visually
+/// identical input is stored and compared the same way
+pub(crate) fn normalize_input(text: &str) -> String {
+    text.nfc().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Folded text with runs of whitespace collapsed to single spaces
+fn fold_words(text: &str) -> String {
+    fold(text).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Normalizes an Italian headword for comparison: case, accents and leading article
+pub(crate) fn normalize_headword(headword: &str) -> String {
+    let folded = fold_words(headword);
+
+    let without_article = ITALIAN_ARTICLES.iter()
+        .chain(ELIDED_ARTICLES)
+        .find_map(|article| folded.strip_prefix(article))
+        .unwrap_or(&folded);
+
+    without_article.trim().to_string()
+}
+
+/// Normalizes an English translation for comparison ("To Run" -> "run")
+pub(crate) fn normalize_translation(translation: &str) -> String {
+    let folded = fold_words(translation);
+
+    let without_prefix = ENGLISH_PREFIXES.iter()
+        .find_map(|prefix| folded.strip_prefix(prefix))
+        .unwrap_or(&folded);
+
+    without_prefix.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_input_composes_and_collapses_whitespace() {
+        assert_eq!(normalize_input("  Se\u{301}rgio \t Rossi\n"), "S\u{e9}rgio Rossi");
+        assert_eq!(normalize_input("Se\u{301}rgio"), normalize_input("S\u{e9}rgio"));
+        assert_eq!(normalize_input("   "), "");
+    }
+
+    #[test]
+    fn fold_removes_case_and_accents() {
+        assert_eq!(fold("Perché"), "perche");
+        assert_eq!(fold("CITTÀ"), "citta");
+    }
+
+    #[test]
+    fn headwords_lose_their_article() {
+        assert_eq!(normalize_headword("Il  Gatto"), "gatto");
+        assert_eq!(normalize_headword("l'amica"), "amica");
+        assert_eq!(normalize_headword("un’isola"), "isola");
+        assert_eq!(normalize_headword("lì"), "li");
+    }
+
+    #[test]
+    fn translations_lose_their_prefix() {
+        assert_eq!(normalize_translation("To Run"), "run");
+        assert_eq!(normalize_translation("the  house"), "house");
+        assert_eq!(normalize_translation("anthem"), "anthem");
+    }
+}
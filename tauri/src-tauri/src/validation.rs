@@ -0,0 +1,262 @@
+// validation.rs - Normalization and validation of the learner's profile fields
+use crate::error::{AppError, FieldError};
+use crate::text::normalize_input;
+use crate::PreferenceResult;
+
+const MAX_FULL_NAME_LENGTH: usize = 100;
+const MIN_USERNAME_LENGTH: usize = 3;
+const MAX_USERNAME_LENGTH: usize = 32;
+
+/// Limits from RFC 5321
+const MAX_EMAIL_LENGTH: usize = 254;
+const MAX_EMAIL_LOCAL_LENGTH: usize = 64;
+const MAX_DOMAIN_LABEL_LENGTH: usize = 63;
+
+/// Characters allowed in a username besides letters and digits
+const USERNAME_SYMBOLS: &[char] = &['.', '_', '-'];
+
+/// Characters allowed in the local part of an email besides ASCII letters and digits
+const EMAIL_LOCAL_SYMBOLS: &str = "!#$%&'*+/=?^_`{|}~.-";
+
+/// Normalized profile fields that passed validation
+#[derive(Debug)]
+pub(crate) struct ProfileFields {
+    pub full_name: String,
+    pub username: String,
+    pub email: String,
+}
+
+/// Key under which usernames are compared, so "Ada" and "ada" count as the same
+pub(crate) fn username_key(username: &str) -> String {
+    normalize_input(username).to_lowercase()
+}
+
+fn check_full_name(full_name: &str) -> Result<(), String> {
+    if full_name.is_empty() {
+        return Err("Full name is required".to_string());
+    }
+
+    if full_name.chars().count() > MAX_FULL_NAME_LENGTH {
+        return Err(format!("Full name cannot be longer than {} characters", MAX_FULL_NAME_LENGTH));
+    }
+
+    if full_name.chars().any(char::is_control) {
+        return Err("Full name cannot contain control characters".to_string());
+    }
+
+    Ok(())
+}
+
+fn check_username(username: &str, taken_usernames: &[String]) -> Result<(), String> {
+    if username.is_empty() {
+        return Err("Username is required".to_string());
+    }
+
+    let length = username.chars().count();
+    if !(MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&length) {
+        return Err(format!(
+            "Username must be {} to {} characters long",
+            MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH
+        ));
+    }
+
+    let allowed = |character: char| character.is_alphanumeric() || USERNAME_SYMBOLS.contains(&character);
+    if !username.chars().all(allowed) {
+        return Err("Username can only contain letters, digits, '.', '_' and '-'".to_string());
+    }
+
+    if !username.starts_with(char::is_alphanumeric) {
+        return Err("Username must start with a letter or digit".to_string());
+    }
+
+    let key = username_key(username);
+    if taken_usernames.iter().any(|taken| username_key(taken) == key) {
+        return Err(format!("Username '{}' is already used by another profile", username));
+    }
+
+    Ok(())
+}
+
+/// Practical email syntax check: a dot-atom local part and a domain of at least
+/// two LDH labels with an alphabetic top-level domain. Quoted local parts and
+/// address literals are not accepted.
+fn check_email(email: &str) -> Result<(), String> {
+    if email.is_empty() {
+        return Err("Email is required".to_string());
+    }
+
+    let invalid = || Err(format!("'{}' is not a valid email address", email));
+
+    if email.len() > MAX_EMAIL_LENGTH {
+        return Err(format!("Email cannot be longer than {} characters", MAX_EMAIL_LENGTH));
+    }
+
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        return invalid();
+    };
+
+    let local_is_valid = !local.is_empty()
+        && local.len() <= MAX_EMAIL_LOCAL_LENGTH
+        && local.split('.').all(|atom| !atom.is_empty())
+        && local.chars().all(|character| character.is_ascii_alphanumeric() || EMAIL_LOCAL_SYMBOLS.contains(character));
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let domain_is_valid = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= MAX_DOMAIN_LABEL_LENGTH
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|character| character.is_ascii_alphanumeric() || character == '-')
+        })
+        && labels.last().is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|character| character.is_ascii_alphabetic()));
+
+    if !local_is_valid || !domain_is_valid {
+        return invalid();
+    }
+
+    Ok(())
+}
+
+/// Normalizes the profile fields and checks them all, returning every invalid
+/// field at once. `taken_usernames` are the usernames of the other local profiles.
+pub(crate) fn validate_profile(
+    full_name: &str,
+    username: &str,
+    email: &str,
+    taken_usernames: &[String],
+) -> PreferenceResult<ProfileFields> {
+    let full_name = normalize_input(full_name);
+    let username = normalize_input(username);
+
+    // Domains are case-insensitive; the local part is kept as typed
+    let email = normalize_input(email);
+    let email = match email.rsplit_once('@') {
+        Some((local, domain)) => format!("{}@{}", local, domain.to_ascii_lowercase()),
+        None => email,
+    };
+
+    let errors: Vec<FieldError> = [
+        ("fullName", check_full_name(&full_name)),
+        ("username", check_username(&username, taken_usernames)),
+        ("email", check_email(&email)),
+    ]
+    .into_iter()
+    .filter_map(|(field, result)| result.err().map(|message| FieldError { field: field.to_string(), message }))
+    .collect();
+
+    if !errors.is_empty() {
+        return Err(AppError::validation(errors));
+    }
+
+    Ok(ProfileFields { full_name, username, email })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_errors(username: &str, email: &str, taken_usernames: &[String]) -> Vec<String> {
+        match validate_profile("Ada Rossi", username, email, taken_usernames) {
+            Ok(_) => Vec::new(),
+            Err(AppError::Validation { errors, .. }) => errors.into_iter().map(|error| error.field).collect(),
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn username_length_boundaries() {
+        for (username, valid) in [
+            ("ab", false),
+            ("abc", true),
+            (&"a".repeat(32)[..], true),
+            (&"a".repeat(33)[..], false),
+            ("àèì", true),
+            ("", false),
+        ] {
+            let errors = field_errors(username, "ada@example.com", &[]);
+            assert_eq!(errors.is_empty(), valid, "{:?}", username);
+        }
+    }
+
+    #[test]
+    fn username_characters() {
+        for username in ["ada.rossi", "ada_rossi", "ada-rossi", "Ada2024", "Ànna"] {
+            assert!(field_errors(username, "ada@example.com", &[]).is_empty(), "{}", username);
+        }
+
+        for username in ["ada rossi", "ada@rossi", "ada!", "_ada", ".ada", "ada/rossi"] {
+            assert_eq!(field_errors(username, "ada@example.com", &[]), ["username"], "{}", username);
+        }
+    }
+
+    #[test]
+    fn usernames_are_unique_across_profiles() {
+        let taken = vec!["Ada".to_string(), "Se\u{301}rgio".to_string()];
+
+        assert_eq!(field_errors("ada", "ada@example.com", &taken), ["username"]);
+        // "Sérgio" typed precomposed matches the decomposed spelling of another profile
+        assert_eq!(field_errors("S\u{e9}rgio", "ada@example.com", &taken), ["username"]);
+        assert!(field_errors("Adalberto", "ada@example.com", &taken).is_empty());
+    }
+
+    #[test]
+    fn fields_are_stored_in_nfc() {
+        let fields = validate_profile("  Ade\u{300}le   Rossi ", "Ade\u{300}le", "Ada@Example.COM", &[]).unwrap();
+
+        assert_eq!(fields.full_name, "Ad\u{e8}le Rossi");
+        assert_eq!(fields.username, "Ad\u{e8}le");
+        assert_eq!(fields.email, "Ada@example.com");
+    }
+
+    #[test]
+    fn accepted_emails() {
+        for email in [
+            "ada@example.com",
+            "ada.rossi@mail.example.it",
+            "ada+italiano@example.co.uk",
+            "o'brien@example.ie",
+            "a@b-c.org",
+        ] {
+            assert!(check_email(email).is_ok(), "{}", email);
+        }
+    }
+
+    #[test]
+    fn rejected_emails() {
+        let long_local = format!("{}@example.com", "a".repeat(65));
+        let long_label = format!("ada@{}.com", "a".repeat(64));
+
+        for email in [
+            "",
+            "ada",
+            "ada@",
+            "@example.com",
+            "ada@example",
+            "ada@example.c",
+            "ada@example.123",
+            "ada..rossi@example.com",
+            ".ada@example.com",
+            "ada rossi@example.com",
+            "ada@-example.com",
+            "ada@example-.com",
+            "ada@exa_mple.com",
+            "\"ada\"@example.com",
+            "ada@[127.0.0.1]",
+            &long_local,
+            &long_label,
+        ] {
+            assert!(check_email(email).is_err(), "{}", email);
+        }
+    }
+
+    #[test]
+    fn reports_every_invalid_field_at_once() {
+        let Err(AppError::Validation { errors, .. }) = validate_profile("", "a", "nope", &[]) else {
+            panic!("expected a validation error");
+        };
+
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, ["fullName", "username", "email"]);
+    }
+}